@@ -0,0 +1,157 @@
+//! The `#[derive(MenuEnum)]` macro for
+//! [cartographer-rs](https://crates.io/crates/cartographer-rs). Use it through cartographer-rs's
+//! `derive` feature, rather than depending on this crate directly.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::Parse;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, LitInt, LitStr, Path, Token};
+
+/// Implements `cartographer_rs::MenuEnum` for an enum of unit variants, making a menu item for
+/// each variant. See the docs of `MenuEnum` in cartographer-rs for the `#[menu(...)]` attributes.
+/// The generated code uses cartographer-rs as `::cartographer_rs`, unless the enum has a
+/// `#[menu(crate = path)]` attribute
+#[proc_macro_derive(MenuEnum, attributes(menu))]
+pub fn derive_menu_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// What the `#[menu(...)]` attribute of the enum asks for
+struct EnumOptions {
+    /// The path to cartographer-rs, in case it was renamed or is used through another crate
+    krate: Path,
+}
+
+impl EnumOptions {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut options = EnumOptions {
+            krate: parse_quote!(::cartographer_rs),
+        };
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("menu"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    options.krate = meta.value()?.parse()?;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `crate = path`"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// What the `#[menu(...)]` attribute of a variant asks for
+#[derive(Default)]
+struct VariantOptions {
+    label: Option<LitStr>,
+    aliases: Vec<LitStr>,
+    hidden: bool,
+    position: Option<LitInt>,
+}
+
+impl VariantOptions {
+    fn parse(variant: &syn::Variant) -> syn::Result<Self> {
+        let mut options = VariantOptions::default();
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("menu"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("label") {
+                    options.label = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("aliases") {
+                    let value = meta.value()?;
+                    let content;
+                    syn::bracketed!(content in value);
+                    let aliases = content.parse_terminated(<LitStr as Parse>::parse, Token![,])?;
+                    options.aliases.extend(aliases);
+                } else if meta.path.is_ident("hidden") {
+                    options.hidden = true;
+                } else if meta.path.is_ident("position") {
+                    options.position = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error(
+                        "expected `label = \"...\"`, `aliases = [...]`, `hidden` or `position = N`",
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "MenuEnum can only be derived for enums",
+        ));
+    };
+
+    let krate = EnumOptions::parse(&input)?.krate;
+    let mut items = Vec::new();
+    let mut arms = Vec::new();
+    for (index, variant) in data.variants.iter().enumerate() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.span(),
+                "MenuEnum variants can't have fields",
+            ));
+        }
+        let options = VariantOptions::parse(variant)?;
+
+        let ident = &variant.ident;
+        let id = ident.to_string();
+        let label = match options.label {
+            Some(label) => label.value(),
+            None => id.clone(),
+        };
+        let visible = !options.hidden;
+        let mut item = quote! {
+            #krate::MenuItem::new(::std::string::String::from(#label))
+                .id(::std::string::String::from(#id))
+                .visible_at_rest(#visible)
+        };
+        if let Some(position) = options.position {
+            item = quote! { #item.at_rest_position(#position) };
+        }
+        if !options.aliases.is_empty() {
+            let aliases = options.aliases;
+            item = quote! {
+                #item.add_alternative_match(::std::vec![
+                    #(::std::string::String::from(#aliases)),*
+                ])
+            };
+        }
+        items.push(item);
+        arms.push(quote! { #index => ::std::option::Option::Some(Self::#ident), });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::MenuEnum for #name #type_generics #where_clause {
+            fn menu_items() -> ::std::vec::Vec<#krate::MenuItem> {
+                ::std::vec![#(#items),*]
+            }
+
+            fn from_index(index: usize) -> ::std::option::Option<Self> {
+                match index {
+                    #(#arms)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    })
+}
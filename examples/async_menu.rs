@@ -0,0 +1,36 @@
+use cartographer_rs::{Error, Menu, MenuItem, MenuOptions};
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() {
+    let opts = MenuOptions::new().select_key(console::Key::Tab);
+
+    // Items are sent from an async task while the menu is open
+    let (sender, receiver) = std::sync::mpsc::channel();
+    tokio::spawn(async move {
+        for i in 1..=20 {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            if sender.send(MenuItem::new(format!("Job #{}", i))).is_err() {
+                break;
+            }
+        }
+    });
+
+    let menu = Menu::new(
+        "Pick some jobs, you have 15 seconds: ".to_string(),
+        vec![],
+        Some(opts),
+    )
+    .stream(receiver);
+
+    tokio::select! {
+        selection = menu.serve_async() => match selection {
+            Ok(selection) => println!("\nYou Selected:\n{:?}", selection),
+            Err(Error::Cancelled) => {}
+            Err(err) => panic!("{}", err),
+        },
+        _ = tokio::time::sleep(Duration::from_secs(15)) => {
+            println!("Out of time!");
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use cartographer_rs::{menu, menu_item, Error, MenuOptions};
+
+enum Command {
+    Build,
+    Test,
+    Deploy(&'static str),
+    Quit,
+}
+
+fn main() {
+    let options = MenuOptions::new().only_one_selection(true);
+
+    let menu = menu!(
+        "Run: ",
+        options.clone(),
+        [
+            section "Develop" => [
+                menu_item!("Build", value = Command::Build, description = "cargo build"),
+                menu_item!("Test", value = Command::Test, description = "cargo test", aliases = ["check"]),
+            ],
+            section "Release" => [
+                submenu menu_item!("Deploy", description = "pick an environment") => menu!(
+                    "Deploy to: ",
+                    options,
+                    [
+                        menu_item!("staging", value = Command::Deploy("staging")),
+                        menu_item!("production", value = Command::Deploy("production"), aliases = ["prod"]),
+                    ]
+                ),
+            ],
+            menu_item!("Quit", pinned, value = Command::Quit),
+        ]
+    );
+
+    let selection = match menu.serve_selection() {
+        Ok(selection) => selection.unwrap_or_default(),
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+
+    for selected in selection {
+        match selected.item().get_value::<Command>() {
+            Some(Command::Build) => println!("Building…"),
+            Some(Command::Test) => println!("Testing…"),
+            Some(Command::Deploy(environment)) => println!("Deploying to {}…", environment),
+            Some(Command::Quit) | None => {}
+        }
+    }
+}
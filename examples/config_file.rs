@@ -0,0 +1,14 @@
+use cartographer_rs::{Error, Menu};
+
+fn main() {
+    // Edit config_file.toml to change the menu without recompiling
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/config_file.toml");
+    let menu = Menu::from_file(path).unwrap();
+
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection,
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+    println!("\nYou Selected:\n{:?}", usr_selection);
+}
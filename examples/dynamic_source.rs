@@ -0,0 +1,30 @@
+use cartographer_rs::{Error, Menu, MenuItem, MenuOptions};
+use std::time::Duration;
+
+/// Stands in for a database or search index that is too big to load into the menu
+fn lookup(query: &str) -> Vec<MenuItem> {
+    // Pretend the lookup takes a while
+    std::thread::sleep(Duration::from_millis(200));
+
+    (0..1_000_000)
+        .map(|i| format!("order-{:07}", i))
+        .filter(|name| name.contains(query.trim()))
+        .take(20)
+        .map(MenuItem::new)
+        .collect()
+}
+
+fn main() {
+    let opts = MenuOptions::new()
+        .select_key(console::Key::Tab)
+        .source_debounce(Duration::from_millis(250));
+
+    let menu = Menu::new("Look up an order: ".to_string(), vec![], Some(opts)).source(lookup);
+
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection,
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+    println!("\nYou Selected:\n{:?}", usr_selection);
+}
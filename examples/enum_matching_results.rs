@@ -1,4 +1,4 @@
-use cartographer_rs::{menu, menu_item, MenuOptions};
+use cartographer_rs::{menu, menu_item, Error, MenuOptions};
 use enum_variants_strings::EnumVariantsStrings;
 
 #[derive(Debug, PartialEq, EnumVariantsStrings)]
@@ -23,7 +23,11 @@ fn main() {
         ]
     );
 
-    let usr_selection = menu.serve().unwrap().unwrap();
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection.unwrap(),
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
     let mut usr_enum: Vec<MenuElements> = Vec::new();
 
     for i in usr_selection {
@@ -0,0 +1,33 @@
+use cartographer_rs::{Error, MenuEnum, MenuOptions};
+
+#[derive(Debug, MenuEnum)]
+enum MenuElements {
+    #[menu(label = "The first one", position = 1)]
+    First,
+    #[menu(label = "The second one", aliases = ["2", "two"])]
+    Second,
+    #[menu(position = 0)]
+    Third,
+    #[menu(hidden)]
+    Fourth,
+}
+
+fn main() {
+    let options = MenuOptions::new().cursor("➤").selected_indicator("✓");
+
+    let menu = MenuElements::menu("Pick some variants: ", Some(options));
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection.unwrap_or_default(),
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+
+    for element in usr_selection {
+        match element {
+            MenuElements::First => println!("You chose the first one!"),
+            MenuElements::Second => println!("The second one was chosen"),
+            MenuElements::Third => println!("3333333333333"),
+            MenuElements::Fourth => println!("You found the hidden one"),
+        }
+    }
+}
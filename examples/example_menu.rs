@@ -1,4 +1,4 @@
-use cartographer_rs::{menu, menu_item, MenuOptions};
+use cartographer_rs::{menu, menu_item, Error, MenuOptions};
 
 fn main() {
     let options = MenuOptions::new().cursor("➤").selected_indicator("✓");
@@ -14,6 +14,10 @@ fn main() {
         ]
     );
 
-    let usr_selection = menu.serve().unwrap().unwrap();
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection.unwrap(),
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
     println!("\nYou Selected:\n{:?}", usr_selection);
 }
@@ -0,0 +1,28 @@
+use cartographer_rs::{Error, Menu, MenuItem, MenuLayout, MenuOptions};
+
+fn main() {
+    let opts = MenuOptions::new()
+        .layout(MenuLayout::Grid)
+        .max_lines_visible(4);
+
+    let languages = [
+        "en", "de", "fr", "es", "it", "pt", "nl", "sv", "no", "da", "fi", "pl", "cs", "sk", "hu",
+        "ro", "bg", "el", "tr", "ru", "uk", "ja", "ko", "zh", "vi", "th", "id", "ms", "hi", "ar",
+    ];
+
+    let menu = Menu::new(
+        "Arrow keys move in every direction: ".to_string(),
+        languages
+            .iter()
+            .map(|code| MenuItem::new(code.to_string()))
+            .collect(),
+        Some(opts),
+    );
+
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection,
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+    println!("\nYou Selected:\n{:?}", usr_selection);
+}
@@ -1,4 +1,4 @@
-use cartographer_rs::{menu, menu_item, MenuOptions};
+use cartographer_rs::{menu, menu_item, Error, MenuOptions};
 
 fn main() {
     let opts = MenuOptions::new().minimum_search_threshold(0.1);
@@ -26,7 +26,11 @@ fn main() {
         ]
     );
 
-    let usr_selected = menu.serve().unwrap().unwrap();
+    let usr_selected = match menu.serve() {
+        Ok(selection) => selection.unwrap(),
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
 
     println!("\nYou Selected:\n{:?}", usr_selected);
 }
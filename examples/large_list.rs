@@ -0,0 +1,41 @@
+use cartographer_rs::{Error, Menu, MenuItem, MenuOptions};
+
+fn main() {
+    let opts = MenuOptions::new()
+        .max_lines_visible(10)
+        .select_key(console::Key::Tab);
+
+    let regions = ["eu-west", "eu-central", "us-east", "us-west", "ap-south"];
+    let services = [
+        "api",
+        "worker",
+        "cache",
+        "db",
+        "gateway",
+        "scheduler",
+        "auth",
+        "search",
+    ];
+
+    // 100,000 items, still searched as fast as you can type
+    let items = (0..100_000)
+        .map(|i| {
+            let name = format!(
+                "{}-{}-{:05}",
+                regions[i % regions.len()],
+                services[(i / regions.len()) % services.len()],
+                i
+            );
+            MenuItem::new(name)
+        })
+        .collect();
+
+    let menu = Menu::new("Search 100,000 hosts: ".to_string(), items, Some(opts));
+
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection,
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+    println!("\nYou Selected:\n{:?}", usr_selection);
+}
@@ -1,16 +1,20 @@
-use cartographer_rs::{Menu, MenuItem};
+use cartographer_rs::{Error, Menu, MenuItem};
 
 fn main() {
-    let mut menu_items: Vec<MenuItem> = Vec::new();
-
-    menu_items.push(MenuItem::new("You can".to_string()));
-    menu_items.push(MenuItem::new("Manually".to_string()));
-    menu_items.push(MenuItem::new("Make A".to_string()));
-    menu_items.push(MenuItem::new("Menu with".to_string()));
-    menu_items.push(MenuItem::new("Pure Structs".to_string()));
+    let menu_items: Vec<MenuItem> = vec![
+        MenuItem::new("You can".to_string()),
+        MenuItem::new("Manually".to_string()),
+        MenuItem::new("Make A".to_string()),
+        MenuItem::new("Menu with".to_string()),
+        MenuItem::new("Pure Structs".to_string()),
+    ];
 
     let menu = Menu::new("It is wordy though: ".to_string(), menu_items, None);
 
-    let selection = menu.serve().unwrap().unwrap();
+    let selection = match menu.serve() {
+        Ok(selection) => selection.unwrap(),
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
     println!("\n{:?}", selection);
 }
@@ -0,0 +1,29 @@
+use cartographer_rs::{menu, menu_item, Error, MenuOptions, PreviewPosition};
+
+fn main() {
+    let opts = MenuOptions::new()
+        .preview_position(PreviewPosition::Right)
+        .preview_lines(10);
+
+    let menu = menu!(
+        "Preview the examples, PageUp/PageDown scrolls: ",
+        opts,
+        [
+            menu_item!("examples/example_menu.rs"),
+            menu_item!("examples/hidden_items.rs"),
+            menu_item!("examples/manual_menu.rs"),
+            menu_item!("examples/sub_menus.rs"),
+            menu_item!("examples/preview_pane.rs")
+        ]
+    )
+    .preview(|item| {
+        std::fs::read_to_string(item.name()).unwrap_or_else(|err| format!("Couldn't read: {err}"))
+    });
+
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection,
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+    println!("\nYou Selected:\n{:?}", usr_selection);
+}
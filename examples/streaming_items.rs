@@ -0,0 +1,28 @@
+use cartographer_rs::{Error, Menu, MenuItem, MenuOptions};
+use std::time::Duration;
+
+fn main() {
+    let opts = MenuOptions::new().select_key(console::Key::Tab);
+
+    // Pretend that each item takes a while to look up
+    let items = (1..=5_000).map(|i| {
+        if i % 100 == 0 {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        MenuItem::new(format!("package-{}", i))
+    });
+
+    let menu = Menu::new(
+        "Items show up as they load: ".to_string(),
+        vec![],
+        Some(opts),
+    )
+    .stream_iter(items);
+
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection,
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+    println!("\nYou Selected:\n{:?}", usr_selection);
+}
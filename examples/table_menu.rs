@@ -0,0 +1,38 @@
+use cartographer_rs::{Column, ColumnAlignment, Error, Menu, MenuItem, MenuOptions};
+
+fn main() {
+    let servers = [
+        ["api-gateway", "eu-west", "running", "3d"],
+        ["api-worker", "eu-west", "stopped", "12d"],
+        ["billing", "us-east", "running", "40d"],
+        ["café-search", "ap-south", "running", "1d"],
+        ["reports", "us-east", "degraded", "7h"],
+    ];
+
+    let items = servers
+        .iter()
+        .map(|row| {
+            MenuItem::new(row[0].to_string()).cells(row.iter().map(|x| x.to_string()).collect())
+        })
+        .collect();
+
+    // Tab selects, so that spaces can be used to search more than one column
+    let opts = MenuOptions::new().select_key(console::Key::Tab);
+
+    let menu =
+        Menu::new("Servers, try `region:eu`: ".to_string(), items, Some(opts)).columns(vec![
+            Column::new("Name"),
+            Column::new("Region"),
+            Column::new("Status"),
+            Column::new("Age")
+                .alignment(ColumnAlignment::Right)
+                .searchable(false),
+        ]);
+
+    let usr_selection = match menu.serve() {
+        Ok(selection) => selection,
+        Err(Error::Cancelled) => return,
+        Err(err) => panic!("{}", err),
+    };
+    println!("\nYou Selected:\n{:?}", usr_selection);
+}
@@ -0,0 +1,24 @@
+use cartographer_rs::{menu, menu_item, Error, MenuOptions};
+use std::time::Duration;
+
+fn main() {
+    let opts = MenuOptions::new()
+        .timeout(Duration::from_secs(5))
+        .timeout_default("staging");
+
+    let menu = menu!(
+        "Deploy to: ",
+        opts,
+        [
+            menu_item!("staging"),
+            menu_item!("production"),
+            menu_item!("local")
+        ]
+    );
+
+    match menu.serve() {
+        Ok(selection) => println!("\nYou Selected:\n{:?}", selection),
+        Err(Error::Timeout(selection)) => println!("\nTimed out, going with:\n{:?}", selection),
+        Err(err) => panic!("{}", err),
+    }
+}
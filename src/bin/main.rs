@@ -1,17 +1,301 @@
-use cartographer_rs::{menu, menu_item};
-
-extern crate cartographer_rs;
-
-fn main() {
-    let menu = menu!(
-        "Pick a number: ",
-        [
-            menu_item!("Item Number 1", true, 1),
-            menu_item!("Item Number 2", false, 2, ["death"]),
-            menu_item!("Item Number 3", true, 3),
-            menu_item!("Item Number 5", true, 5, ["80", "5"])
-        ]
-    );
-
-    menu.serve().unwrap();
+use cartographer_rs::{Error, KeyName, Menu, MenuItem, MenuOptions, OutputFormat};
+use std::io::{BufRead, IsTerminal, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: cartographer [OPTIONS]
+       cartographer shell-init <bash|zsh|fish>
+
+Reads items from standard input, one per line, lets you pick from them in a searchable menu,
+and prints what you picked to standard output, one item per line. The menu is drawn on the
+terminal, so the output can be piped into another program.
+
+Commands:
+  shell-init <SHELL>        Print key bindings for bash, zsh or fish that use cartographer to
+                            pick files (Ctrl-T), cd into a directory (Alt-C), and search the
+                            history (Ctrl-R). For example, add this to ~/.bashrc:
+                                eval \"$(cartographer shell-init bash)\"
+
+Options:
+  -p, --prompt <TEXT>       The text in front of the search [default: \"> \"]
+  -m, --multi               Pick any number of items with the select key, then press enter.
+                            Enter picks the item under the cursor if nothing was picked
+      --select-key <KEY>    The key that picks an item, like `tab` or `ctrl-s` [default: tab]
+  -t, --threshold <NUMBER>  How closely an item has to match the search to be shown, from 0 up
+                            to (but not including) 1 [default: 0.005]
+      --cursor <TEXT>       The cursor in front of the current item [default: >]
+      --indicator <TEXT>    The mark in front of picked items [default: X]
+      --json                Read items as JSON lines, like
+                            {\"label\": \"production\", \"aliases\": [\"prod\"], \"hidden\": false}
+                            Hidden items are only shown when they match the search. Items can
+                            also have a \"payload\" of any JSON, for --output json-payloads
+      --output <FORMAT>     How to print what was picked [default: lines]
+                              lines          One item per line
+                              nul            Each item followed by a NUL byte, for `xargs -0`
+                              shell          Quoted for the shell, separated by spaces
+                              json-labels    A JSON array of the items
+                              json-indices   A JSON array of the lines the items were on in the
+                                             input, counting from 0
+                              json-payloads  A JSON array of the items' payloads, or null for
+                                             items without one
+  -0, --print0              The same as --output nul
+      --delimiter <TEXT>    Print the items separated by TEXT, on one line
+  -h, --help                Print this help
+  -V, --version             Print the version
+
+Exit status:
+  0    Something was picked
+  1    Nothing was picked
+  2    There was an error
+  130  The menu was closed with Esc";
+
+/// Everything that can be set on the command line
+struct Args {
+    prompt: String,
+    multi: bool,
+    select_key: KeyName,
+    threshold: f32,
+    cursor: String,
+    indicator: String,
+    json: bool,
+    output: Output,
+}
+
+/// How the selection is printed
+enum Output {
+    Format(OutputFormat),
+    /// The lines of the input that the items were on. This isn't [`OutputFormat::JsonIndices`],
+    /// as blank lines aren't items, but they still count
+    JsonLines,
+    /// The `payload`s of `--json` items. This can't be an [`OutputFormat`], as the library doesn't
+    /// know about payloads
+    JsonPayloads,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            prompt: "> ".to_string(),
+            multi: false,
+            select_key: KeyName(console::Key::Tab),
+            threshold: 0.005,
+            cursor: ">".to_string(),
+            indicator: "X".to_string(),
+            json: false,
+            output: Output::Format(OutputFormat::Lines),
+        }
+    }
+}
+
+/// What the command line asked for
+enum Command {
+    Menu(Args),
+    ShellInit(&'static str),
+    Help,
+    Version,
+}
+
+/// The key bindings printed by `shell-init`, for each shell
+const SHELL_SCRIPTS: [(&str, &str); 3] = [
+    ("bash", include_str!("shell/init.bash")),
+    ("zsh", include_str!("shell/init.zsh")),
+    ("fish", include_str!("shell/init.fish")),
+];
+
+/// A line of input with `--json`
+#[derive(serde::Deserialize)]
+struct JsonItem {
+    label: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.peekable();
+    if args.peek().map(String::as_str) == Some("shell-init") {
+        args.next();
+        let shell = args
+            .next()
+            .ok_or("`shell-init` needs a shell: bash, zsh or fish")?;
+        if let Some(extra) = args.next() {
+            return Err(format!("unexpected argument `{}`", extra));
+        }
+        return match SHELL_SCRIPTS.iter().find(|(name, _)| *name == shell) {
+            Some((_, script)) => Ok(Command::ShellInit(script)),
+            None => Err(format!(
+                "`{}` isn't a supported shell, expected bash, zsh or fish",
+                shell
+            )),
+        };
+    }
+
+    let mut parsed = Args::default();
+
+    while let Some(arg) = args.next() {
+        // Allow `--flag=value` as well as `--flag value`
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || match inline_value {
+            Some(value) => Ok(value.to_string()),
+            None => args
+                .next()
+                .ok_or_else(|| format!("`{}` needs a value", flag)),
+        };
+
+        match flag.as_str() {
+            "-p" | "--prompt" => parsed.prompt = value()?,
+            "-m" | "--multi" => parsed.multi = true,
+            "--select-key" => {
+                parsed.select_key = value()?.parse().map_err(|err: Error| err.to_string())?
+            }
+            "-t" | "--threshold" => {
+                let text = value()?;
+                parsed.threshold = text
+                    .parse()
+                    .map_err(|_| format!("`{}` isn't a number", text))?;
+            }
+            "--cursor" => parsed.cursor = value()?,
+            "--indicator" => parsed.indicator = value()?,
+            "--json" => parsed.json = true,
+            "--output" => {
+                parsed.output = match value()?.as_str() {
+                    "lines" => Output::Format(OutputFormat::Lines),
+                    "nul" => Output::Format(OutputFormat::Nul),
+                    "shell" => Output::Format(OutputFormat::ShellQuoted),
+                    "json-labels" => Output::Format(OutputFormat::JsonLabels),
+                    "json-indices" => Output::JsonLines,
+                    "json-payloads" => Output::JsonPayloads,
+                    other => return Err(format!("`{}` isn't an output format", other)),
+                }
+            }
+            "-0" | "--print0" => parsed.output = Output::Format(OutputFormat::Nul),
+            "--delimiter" => parsed.output = Output::Format(OutputFormat::Delimited(value()?)),
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+    Ok(Command::Menu(parsed))
+}
+
+/// The menu's items, as read from standard input
+#[derive(Default)]
+struct Input {
+    items: Vec<MenuItem>,
+    /// The `payload` of each item, or null
+    payloads: Vec<serde_json::Value>,
+    /// The line each item was on, counting from 0
+    lines: Vec<usize>,
+}
+
+/// Read the menu's items from standard input, skipping blank lines
+fn read_items(json: bool) -> Result<Input, String> {
+    let mut input = Input::default();
+    for (number, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line.map_err(|err| format!("couldn't read the items: {}", err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if json {
+            let item: JsonItem = serde_json::from_str(&line)
+                .map_err(|err| format!("line {}: {}", number + 1, err))?;
+            let mut menu_item = MenuItem::new(item.label).visible_at_rest(!item.hidden);
+            if !item.aliases.is_empty() {
+                menu_item = menu_item.add_alternative_match(item.aliases);
+            }
+            input.items.push(menu_item);
+            input.payloads.push(item.payload);
+        } else {
+            input.items.push(MenuItem::new(line));
+            input.payloads.push(serde_json::Value::Null);
+        }
+        input.lines.push(number);
+    }
+    Ok(input)
+}
+
+fn run(args: Args) -> Result<ExitCode, String> {
+    if std::io::stdin().is_terminal() {
+        return Err("pipe the items to pick from into standard input".to_string());
+    }
+    let Input {
+        items,
+        mut payloads,
+        lines,
+    } = read_items(args.json)?;
+
+    let options = MenuOptions::new()
+        .cursor(&args.cursor)
+        .cursor_width(console::measure_text_width(&args.cursor))
+        .selected_indicator(&args.indicator)
+        .selected_indicator_width(console::measure_text_width(&args.indicator))
+        .select_key(args.select_key.0)
+        .minimum_search_threshold(args.threshold)
+        .only_one_selection(!args.multi);
+
+    match Menu::new(args.prompt, items, Some(options)).serve_selection() {
+        Ok(Some(selection)) => {
+            let output = match args.output {
+                Output::Format(format @ (OutputFormat::Lines | OutputFormat::Nul)) => {
+                    format.format(&selection)
+                }
+                Output::Format(format) => format!("{}\n", format.format(&selection)),
+                Output::JsonLines => {
+                    let selected: Vec<usize> = selection
+                        .iter()
+                        .map(|selected| lines[selected.index()])
+                        .collect();
+                    format!("{}\n", serde_json::Value::from(selected))
+                }
+                Output::JsonPayloads => {
+                    let selected: Vec<serde_json::Value> = selection
+                        .iter()
+                        .map(|selected| payloads[selected.index()].take())
+                        .collect();
+                    format!("{}\n", serde_json::Value::from(selected))
+                }
+            };
+            print(&output)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Ok(None) => Ok(ExitCode::from(1)),
+        Err(Error::Cancelled) => Ok(ExitCode::from(130)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Print to standard output. It's fine if the program reading it has stopped, like `head` does
+fn print(text: &str) -> Result<(), String> {
+    match std::io::stdout().lock().write_all(text.as_bytes()) {
+        Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => {
+            Err(format!("couldn't write to standard output: {}", err))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn main() -> ExitCode {
+    let result = match parse_args(std::env::args().skip(1)) {
+        Ok(Command::Menu(args)) => run(args),
+        Ok(Command::ShellInit(script)) => print(script).map(|_| ExitCode::SUCCESS),
+        Ok(Command::Help) => print(&format!("{}\n", USAGE)).map(|_| ExitCode::SUCCESS),
+        Ok(Command::Version) => print(&format!("cartographer {}\n", env!("CARGO_PKG_VERSION")))
+            .map(|_| ExitCode::SUCCESS),
+        Err(err) => Err(format!(
+            "{}\n\nSee `cartographer --help` for the options",
+            err
+        )),
+    };
+
+    result.unwrap_or_else(|err| {
+        eprintln!("cartographer: {}", err);
+        ExitCode::from(2)
+    })
 }
@@ -0,0 +1,53 @@
+# cartographer key bindings for bash
+# Add `eval "$(cartographer shell-init bash)"` to ~/.bashrc
+#
+#   Ctrl-T  Pick files and directories, and insert them at the cursor
+#   Alt-C   Pick a directory to cd into
+#   Ctrl-R  Pick a command from the history, and put it on the command line
+#
+# Set CARTOGRAPHER_FILES_COMMAND or CARTOGRAPHER_DIRS_COMMAND to change how the files and
+# directories are listed.
+
+__cartographer_list_files() {
+  if [ -n "$CARTOGRAPHER_FILES_COMMAND" ]; then
+    eval "$CARTOGRAPHER_FILES_COMMAND"
+  else
+    command find -L . -mindepth 1 -name '.*' -prune -o -print 2>/dev/null | command cut -c3-
+  fi
+}
+
+__cartographer_list_dirs() {
+  if [ -n "$CARTOGRAPHER_DIRS_COMMAND" ]; then
+    eval "$CARTOGRAPHER_DIRS_COMMAND"
+  else
+    command find -L . -mindepth 1 -name '.*' -prune -o -type d -print 2>/dev/null | command cut -c3-
+  fi
+}
+
+__cartographer_insert_files() {
+  local selected item
+  selected="$(__cartographer_list_files | command cartographer --multi --prompt 'Files: ' |
+    while IFS= read -r item; do printf '%q ' "$item"; done)"
+  READLINE_LINE="${READLINE_LINE:0:$READLINE_POINT}$selected${READLINE_LINE:$READLINE_POINT}"
+  READLINE_POINT=$((READLINE_POINT + ${#selected}))
+}
+
+__cartographer_cd() {
+  local dir
+  dir="$(__cartographer_list_dirs | command cartographer --prompt 'Directory: ')" &&
+    builtin cd -- "$dir"
+}
+
+__cartographer_history() {
+  local selected
+  selected="$(HISTTIMEFORMAT='' builtin history |
+    command awk '{ sub(/^ *[0-9]+\*? */, ""); lines[NR] = $0 }
+      END { for (i = NR; i > 0; i--) if (!seen[lines[i]]++) print lines[i] }' |
+    command cartographer --prompt 'History: ')" || return
+  READLINE_LINE="$selected"
+  READLINE_POINT=${#selected}
+}
+
+bind -x '"\C-t": __cartographer_insert_files'
+bind -x '"\ec": __cartographer_cd'
+bind -x '"\C-r": __cartographer_history'
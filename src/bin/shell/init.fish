@@ -0,0 +1,47 @@
+# cartographer key bindings for fish
+# Add `cartographer shell-init fish | source` to ~/.config/fish/config.fish
+#
+#   Ctrl-T  Pick files and directories, and insert them at the cursor
+#   Alt-C   Pick a directory to cd into
+#   Ctrl-R  Pick a command from the history, and put it on the command line
+#
+# Set CARTOGRAPHER_FILES_COMMAND or CARTOGRAPHER_DIRS_COMMAND to change how the files and
+# directories are listed.
+
+function __cartographer_list_files
+    if set -q CARTOGRAPHER_FILES_COMMAND
+        eval $CARTOGRAPHER_FILES_COMMAND
+    else
+        command find -L . -mindepth 1 -name '.*' -prune -o -print 2>/dev/null | command cut -c3-
+    end
+end
+
+function __cartographer_list_dirs
+    if set -q CARTOGRAPHER_DIRS_COMMAND
+        eval $CARTOGRAPHER_DIRS_COMMAND
+    else
+        command find -L . -mindepth 1 -name '.*' -prune -o -type d -print 2>/dev/null | command cut -c3-
+    end
+end
+
+function __cartographer_insert_files
+    set -l selected (__cartographer_list_files | command cartographer --multi --prompt 'Files: ')
+    and commandline -i -- (string escape -- $selected | string join ' ')' '
+    commandline -f repaint
+end
+
+function __cartographer_cd
+    set -l dir (__cartographer_list_dirs | command cartographer --prompt 'Directory: ')
+    and builtin cd -- $dir
+    commandline -f repaint
+end
+
+function __cartographer_history
+    set -l selected (builtin history | command cartographer --prompt 'History: ')
+    and commandline -r -- $selected
+    commandline -f repaint
+end
+
+bind \ct __cartographer_insert_files
+bind \ec __cartographer_cd
+bind \cr __cartographer_history
@@ -0,0 +1,85 @@
+use crate::{Error, Menu, MenuItem, MenuOptions};
+use std::marker::PhantomData;
+
+/// An enum whose variants can be picked from a menu. With the `derive` feature, this can be
+/// derived for enums of unit variants with `#[derive(MenuEnum)]`, which makes a [`MenuItem`]
+/// for each variant.
+///
+/// Each item is labeled with the name of its variant, and uses it as its [`id`](MenuItem::id),
+/// unless changed with the `#[menu(...)]` attribute:
+/// - `label = "..."` sets the text the item is shown with
+/// - `aliases = ["...", ...]` adds [alternative matches](MenuItem::add_alternative_match)
+/// - `hidden` only shows the item when it matches the search
+/// - `position = N` sets the item's [`at_rest_position`](MenuItem::at_rest_position)
+///
+/// The generated code refers to this crate as `::cartographer_rs`. If it is renamed in
+/// `Cargo.toml`, or used through another crate that re-exports it, give the path to use with
+/// `#[menu(crate = path)]` on the enum.
+///
+/// ## Example
+#[cfg_attr(feature = "derive", doc = "```no_run")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use cartographer_rs::MenuEnum;
+///
+/// #[derive(Debug, MenuEnum)]
+/// enum Environment {
+///     #[menu(label = "Staging", position = 1)]
+///     Staging,
+///     #[menu(label = "Production", aliases = ["prod", "live"])]
+///     Production,
+///     #[menu(hidden)]
+///     Local,
+/// }
+///
+/// let menu = Environment::menu("Deploy to: ", None);
+/// let environments: Option<Vec<Environment>> = menu.serve().unwrap();
+/// ```
+pub trait MenuEnum: Sized {
+    /// A menu item for each variant, in the order they are declared
+    fn menu_items() -> Vec<MenuItem>;
+
+    /// Get the variant for the item at `index` in [`MenuEnum::menu_items`]
+    fn from_index(index: usize) -> Option<Self>;
+
+    /// Make a menu of the variants. If configuration is `None`, the default is used
+    fn menu(prompt: &str, configuration: Option<MenuOptions>) -> EnumMenu<Self> {
+        EnumMenu {
+            menu: Menu::new(prompt.to_string(), Self::menu_items(), configuration),
+            variants: PhantomData,
+        }
+    }
+}
+
+/// A [`Menu`] of the variants of a [`MenuEnum`], made with [`MenuEnum::menu`]. Serving it
+/// returns the variants that were selected
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnumMenu<T> {
+    menu: Menu,
+    variants: PhantomData<fn() -> T>,
+}
+
+impl<T: MenuEnum> EnumMenu<T> {
+    /// Serve the menu like [`Menu::serve`], returning the selected variants
+    pub fn serve(&self) -> Result<Option<Vec<T>>, Error> {
+        Ok(self.menu.serve_selection()?.map(|selection| {
+            selection
+                .iter()
+                .filter_map(|selected| T::from_index(selected.index()))
+                .collect()
+        }))
+    }
+
+    /// Get the menu the variants are picked from
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    /// Change the menu the variants are picked from, like to give it a
+    /// [`preview`](Menu::preview). Its items should be left as they are
+    pub fn map_menu(self, change: impl FnOnce(Menu) -> Menu) -> Self {
+        EnumMenu {
+            menu: change(self.menu),
+            variants: PhantomData,
+        }
+    }
+}
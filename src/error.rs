@@ -0,0 +1,66 @@
+/// Everything that can go wrong while building or serving a [`Menu`](crate::Menu)
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading keys, drawing the menu, or reading or writing a history file failed
+    Io(std::io::Error),
+
+    /// The menu needs a terminal to draw on, and there isn't one: standard output isn't a
+    /// terminal, and neither is there a controlling terminal
+    NotATerminal,
+
+    /// The user closed the menu with `Esc`, or the future from `Menu::serve_async` was dropped
+    Cancelled,
+
+    /// The menu's [`timeout`](crate::MenuOptions::timeout) ran out. Holds the names of the items
+    /// that were chosen for the user: the ones they had selected, or the
+    /// [`timeout_default`](crate::MenuOptions::timeout_default) if they hadn't selected any
+    Timeout(Vec<String>),
+
+    /// An option was given a value that it can't have, like a negative
+    /// [`minimum_search_threshold`](crate::MenuOptions::minimum_search_threshold)
+    InvalidValue {
+        /// The name of the builder method the value was given to
+        option: &'static str,
+        /// What is wrong with the value
+        reason: String,
+    },
+
+    /// A menu definition or key name couldn't be read, like a config file with a typo in it
+    Parse(String),
+
+    /// The options don't work together, or with the menu they were given to, like a
+    /// [`select_key`](crate::MenuOptions::select_key) of `Enter`
+    InvalidConfiguration(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::NotATerminal => f.write_str("the menu has to be drawn on a terminal"),
+            Error::Cancelled => f.write_str("the menu was cancelled"),
+            Error::Timeout(_) => f.write_str("the menu timed out"),
+            Error::InvalidValue { option, reason } => {
+                write!(f, "invalid value for `{}`: {}", option, reason)
+            }
+            Error::InvalidConfiguration(reason) => write!(f, "invalid configuration: {}", reason),
+            Error::Parse(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -4,8 +4,9 @@
 //! Use the [`menu_item!`](crate::menu_item!) and [`menu!`](crate::menu!) macros for the best effect
 //!
 //! ## Example
-//! ```
+//! ```no_run
 //! use cartographer_rs::{menu, menu_item};
+//! # fn main() -> Result<(), cartographer_rs::Error> {
 //! let menu = menu!(
 //!       "Pick a number: ",
 //!       [
@@ -19,12 +20,110 @@
 //!
 //! // Returns the string of the item given
 //! let usr_selection = menu.serve()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Searching
+//! Typing while a menu is open searches its items. Plain text is fuzzy matched against each
+//! item's name and alternative matches, but the search also understands the same operators as
+//! [fzf](https://github.com/junegunn/fzf#search-syntax):
+//!
+//! | Term       | Shows items that        |
+//! |------------|-------------------------|
+//! | `word`     | fuzzy match `word`      |
+//! | `'word`    | contain `word`          |
+//! | `^word`    | start with `word`       |
+//! | `word$`    | end with `word`         |
+//! | `^word$`   | are exactly `word`      |
+//! | `!word`    | don't contain `word`    |
+//! | `!^word`   | don't start with `word` |
+//! | `!word$`   | don't end with `word`   |
+//!
+//! Terms are separated by spaces and all of them have to match, unless they are joined by a lone
+//! `|`, in which case only one of them has to. For example, `^core go$ | rs$ !test` shows the
+//! items that start with "core", end with "go" or "rs", and don't contain "test".
+//!
+//! A term can be limited to one field by starting it with the field's name and a colon. `alias:`
+//! only looks at an item's alternative matches, and in a menu with
+//! [`columns`](crate::Menu::columns), each searchable column's header is a field name too, e.g.
+//! `region:eu` or `!status:^stop`.
+//!
+//! Searches ignore case by default, which can be changed with
+//! [`MenuOptions::case_sensitivity`](crate::MenuOptions::case_sensitivity), and
+//! [`MenuOptions::normalize_unicode`](crate::MenuOptions::normalize_unicode) makes them ignore
+//! accents too.
+//!
+//! Each item's text is prepared for searching once, when the menu is served, and a search that
+//! only adds terms to an earlier one, like typing "apple pie" after "apple", only looks at the
+//! items that passed it. On long lists, the search is run a chunk of items at a time, so that it
+//! can be cut short when the user types something else, and its progress is shown below the
+//! items.
+//!
+//! ### Ranking
+//! Each item gets a score between 0 and 1 for how well the plain (fuzzy) terms of the search
+//! match its name, and another for its best alternative match or searchable cell. By default,
+//! the item's score is the better of the two, so an item whose alternative match is exactly what
+//! the user typed scores 1, even if its name looks nothing like it. This can be changed with
+//! [`MenuOptions::score_aggregation`](crate::MenuOptions::score_aggregation).
+//!
+//! An item is shown if each plain term scores above the
+//! [`minimum_search_threshold`](crate::MenuOptions::minimum_search_threshold) on its own, and it
+//! passes all of the search's operator and field terms. Results are shown best score first, after
+//! multiplying each score by the item's [`search_weight`](crate::MenuItem::search_weight), and
+//! items with the same score keep the order they have at rest. If nothing matches, the menu
+//! shows its resting items instead.
+//!
+//! At rest, items with an [`at_rest_position`](crate::MenuItem::at_rest_position) are shown
+//! first, in order of their position, followed by the rest in the order they were given.
+//!
+//! [`pinned`](crate::MenuItem::pinned) items are always shown above the rest, both at rest and
+//! in search results.
+//!
+//! If the menu has a selection history (see
+//! [`MenuOptions::history_file`](crate::MenuOptions::history_file)), every item also gets a
+//! frecency between 0 and 1 for how often and how recently it was selected, compared to the
+//! other items. During a search, the frecency times the
+//! [`frecency_weight`](crate::MenuOptions::frecency_weight) is added to the weighted score, and at
+//! rest, items that have been selected before are shown first, most frecent first.
+//!
+//! ## Config files
+//! With the `toml`, `yaml` or `json` feature, a whole menu can be loaded from a file with
+//! `Menu::from_file`, so that its items and options can be changed without recompiling. The
+//! options are named after the [`MenuOptions`] builder methods, and keys are written like `"tab"`
+//! or `"ctrl-r"` (see [`KeyName`]).
+//!
+//! ```toml
+//! prompt = "Deploy to: "
+//! items = ["staging", { visible_name = "production", alternative_matches = ["prod"] }]
+//!
+//! [options]
+//! select_key = "tab"
+//! only_one_selection = true
 //! ```
 //!
 
 /// Contains the Menu and Menu Item structs for configuration
 mod menu;
-pub use menu::{Menu, MenuItem, MenuOptions};
+pub use menu::{
+    CaseSensitivity, Column, ColumnAlignment, FileHistory, HistoryEntry, HistoryStore, KeyName,
+    Menu, MenuEvent, MenuItem, MenuLayout, MenuOptions, MenuSession, PreviewPosition,
+    ScoreAggregation,
+};
+
+/// Contains the crate's Error type
+mod error;
+pub use error::Error;
+
+/// Contains the types for writing out what the user selected
+mod output;
+pub use output::{OutputFormat, Selected};
+
+/// Contains the MenuEnum trait for picking enum variants from a menu
+mod enum_menu;
+#[cfg(feature = "derive")]
+pub use cartographer_derive::MenuEnum;
+pub use enum_menu::{EnumMenu, MenuEnum};
 
 /// Contains the menu! and menu_item! macros
 mod menu_macros;
@@ -1,10 +1,33 @@
+#[cfg(feature = "serde_serialize")]
+mod config;
+mod edit;
+mod events;
+mod history;
+mod input;
 mod interact;
+mod keys;
+mod query;
+mod session;
+
+use crate::Error;
+use events::Hooks;
+pub use events::MenuEvent;
+pub use history::{FileHistory, HistoryEntry, HistoryStore};
+pub use keys::KeyName;
+pub use session::MenuSession;
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[cfg_attr(
     feature = "serde_serialize",
-    derive(serde::Serialize, serde::Deserialize)
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
 )]
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug)]
 /// A data structure representing a line-item in a menu
 ///
 /// The recommended way of constructing these is to use  the [`menu_item!`](crate::menu_item!) macro
@@ -12,19 +35,22 @@ mod interact;
 ///
 /// ## Example
 /// ```
+/// use cartographer_rs::{menu_item, MenuItem};
+///
 /// let menu_item = MenuItem::new("A Menu Item".to_string())
 ///     .visible_at_rest(true)
 ///     .at_rest_position(1);
 ///
 /// // is the same as
 ///
-/// menu_item!("A Menu Item", true, 1);
+/// assert_eq!(menu_item, menu_item!("A Menu Item", true, 1));
 /// ```
 pub struct MenuItem {
     /// The String that will display for this item in the menu
     visible_name: String,
 
     /// Toggles if this item will be shown when no search terms are available
+    #[cfg_attr(feature = "serde_serialize", serde(default = "config::enabled"))]
     visible_at_rest: bool,
 
     /// Optional feature that will let you specify in what order the MenuItems will be
@@ -34,6 +60,127 @@ pub struct MenuItem {
     /// A list of strings that will also be used, in addition to the `visible_name`,
     /// when processing the search results
     alternative_matches: Option<Vec<String>>,
+
+    /// The text of each column when the item is shown in a [`Menu`] with
+    /// [`columns`](Menu::columns)
+    cells: Option<Vec<String>>,
+
+    /// Multiplies the item's search score when ranking the results
+    #[cfg_attr(feature = "serde_serialize", serde(default))]
+    search_weight: SearchWeight,
+
+    /// Keeps the item above all unpinned items whenever it is shown
+    #[cfg_attr(feature = "serde_serialize", serde(default))]
+    pinned: bool,
+
+    /// Identifies the item in the selection history. The `visible_name` is used if this isn't set
+    id: Option<String>,
+
+    /// Shown after the `visible_name`, without being searched
+    description: Option<String>,
+
+    /// The heading the item is listed under when the menu is at rest
+    section: Option<String>,
+
+    /// Any value the item carries, so that it doesn't have to be looked up from the selection
+    #[cfg_attr(feature = "serde_serialize", serde(skip))]
+    value: Option<ItemValue>,
+}
+
+/// A search weight that can be compared and hashed, so that [`MenuItem`] can be too
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SearchWeight(pub(crate) f32);
+
+impl Default for SearchWeight {
+    fn default() -> Self {
+        SearchWeight(1.0)
+    }
+}
+
+impl PartialEq for SearchWeight {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for SearchWeight {}
+
+impl PartialOrd for SearchWeight {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SearchWeight {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl std::hash::Hash for SearchWeight {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+/// A value of any type given to a [`MenuItem`]
+#[derive(Clone)]
+pub(crate) struct ItemValue(Arc<dyn Any + Send + Sync>);
+
+impl std::fmt::Debug for ItemValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ItemValue")
+    }
+}
+
+impl MenuItem {
+    /// Everything that items are compared and hashed by, which is every field but the value, as
+    /// values of any type can't be compared
+    fn compared(&self) -> impl Ord + std::hash::Hash + '_ {
+        (
+            &self.visible_name,
+            self.visible_at_rest,
+            self.at_rest_position,
+            &self.alternative_matches,
+            &self.cells,
+            self.search_weight,
+            self.pinned,
+            &self.id,
+            &self.description,
+            &self.section,
+        )
+    }
+}
+
+impl PartialEq for MenuItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.compared() == other.compared()
+    }
+}
+
+impl Eq for MenuItem {}
+
+impl PartialOrd for MenuItem {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MenuItem {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.compared().cmp(&other.compared())
+    }
+}
+
+impl std::hash::Hash for MenuItem {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.compared().hash(state);
+    }
 }
 
 impl MenuItem {
@@ -44,6 +191,13 @@ impl MenuItem {
             visible_at_rest: true,
             at_rest_position: None,
             alternative_matches: None,
+            cells: None,
+            search_weight: SearchWeight::default(),
+            pinned: false,
+            id: None,
+            description: None,
+            section: None,
+            value: None,
         }
     }
 
@@ -64,15 +218,62 @@ impl MenuItem {
         }
     }
 
+    /// Set how much a [`MenuItem`]'s search score is multiplied by when ranking search results.
+    /// Values above 1.0 move the item up, values below move it down. The weight only changes
+    /// the order, not whether the item matches the search at all.
+    /// The default is: 1.0
+    pub fn search_weight(self, weight: f32) -> Self {
+        MenuItem {
+            search_weight: SearchWeight(weight),
+            ..self
+        }
+    }
+
+    /// Set if a [`MenuItem`] is pinned. Pinned items are shown above all unpinned items, both at
+    /// rest and whenever they match a search, no matter their score
+    pub fn pinned(self, pinned: bool) -> Self {
+        MenuItem { pinned, ..self }
+    }
+
+    /// Set the id that the selection history knows a [`MenuItem`] by. Setting this keeps the
+    /// item's history when its name changes, and tells items with the same name apart
+    pub fn id(self, id: String) -> Self {
+        MenuItem {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    /// Get the id that the selection history knows this [`MenuItem`] by. This is the name,
+    /// unless an [`id`](MenuItem::id) was set
+    pub fn get_id(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.visible_name)
+    }
+
+    /// Get the name that is displayed for this [`MenuItem`]
+    pub fn name(&self) -> &str {
+        self.visible_name.as_str()
+    }
+
+    /// Get the text of this [`MenuItem`]'s table cells. Empty if no cells were set
+    pub fn get_cells(&self) -> &[String] {
+        self.cells.as_deref().unwrap_or_default()
+    }
+
+    /// Set the text of each column for when this [`MenuItem`] is shown in a [`Menu`] with
+    /// [`columns`](Menu::columns). The `visible_name` is still what the menu returns when the
+    /// item is selected
+    pub fn cells(self, cells: Vec<String>) -> Self {
+        MenuItem {
+            cells: Some(cells),
+            ..self
+        }
+    }
+
     /// Set alternative matches for a [`MenuItem`]. These are strings that this item will
     /// match to when searching - in addition to the visible_name
     pub fn add_alternative_match(self, new_matches: Vec<String>) -> Self {
-        let mut cur_matches: Vec<String>;
-        if self.alternative_matches.is_none() {
-            cur_matches = Vec::new();
-        } else {
-            cur_matches = self.alternative_matches.unwrap();
-        }
+        let mut cur_matches = self.alternative_matches.unwrap_or_default();
         for i in new_matches {
             cur_matches.push(i);
         }
@@ -81,10 +282,91 @@ impl MenuItem {
             ..self
         }
     }
+
+    /// Set a description that is shown dimmed after a [`MenuItem`]'s name. Unlike
+    /// [alternative matches](MenuItem::add_alternative_match), it isn't searched.
+    /// Items in a menu with [`columns`](Menu::columns) don't show their description
+    pub fn description(self, description: String) -> Self {
+        MenuItem {
+            description: Some(description),
+            ..self
+        }
+    }
+
+    /// Get this [`MenuItem`]'s description, if it has one
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Set the section a [`MenuItem`] is listed under. When the menu is at rest, a heading is
+    /// drawn above each run of items in the same section, so the items of a section should be
+    /// next to each other. Headings aren't shown during a search, or in the grid layout
+    pub fn section(self, section: String) -> Self {
+        MenuItem {
+            section: Some(section),
+            ..self
+        }
+    }
+
+    /// Get the section this [`MenuItem`] is listed under, if it has one
+    pub fn get_section(&self) -> Option<&str> {
+        self.section.as_deref()
+    }
+
+    /// Give a [`MenuItem`] a value of any type, like the command it stands for, which can be
+    /// taken back out of the selection with [`get_value`](MenuItem::get_value). Values aren't
+    /// (de)serialized, and items are compared and hashed without them
+    ///
+    /// ## Example
+    /// ```
+    /// use cartographer_rs::MenuItem;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Command {
+    ///     Deploy,
+    ///     Rollback,
+    /// }
+    ///
+    /// let item = MenuItem::new("Deploy".to_string()).value(Command::Deploy);
+    /// assert_eq!(item.get_value::<Command>(), Some(&Command::Deploy));
+    /// assert_eq!(item.get_value::<String>(), None);
+    /// ```
+    pub fn value<T: Any + Send + Sync>(self, value: T) -> Self {
+        MenuItem {
+            value: Some(ItemValue(Arc::new(value))),
+            ..self
+        }
+    }
+
+    /// Get this [`MenuItem`]'s value, if it has one of type `T`
+    pub fn get_value<T: Any>(&self) -> Option<&T> {
+        self.value.as_ref()?.0.downcast_ref()
+    }
+}
+
+impl From<&str> for MenuItem {
+    fn from(visible_name: &str) -> Self {
+        MenuItem::new(visible_name.to_string())
+    }
+}
+
+impl From<String> for MenuItem {
+    fn from(visible_name: String) -> Self {
+        MenuItem::new(visible_name)
+    }
 }
 
 /// The Menu struct that contains the information and
 /// functions for displaying the menus
+///
+/// With the `serde_serialize` feature, a menu's prompt, items, options and columns can be
+/// (de)serialized, laid out like they are for `Menu::from_file`. Callbacks, streams and custom
+/// history stores are left out
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
 #[derive(Clone, Debug, PartialEq)]
 pub struct Menu {
     /// The text to be displayed on the same line as user input will be shown.
@@ -92,10 +374,47 @@ pub struct Menu {
     prompt: String,
 
     /// The Vector of [`MenuItem`]s
+    #[cfg_attr(
+        feature = "serde_serialize",
+        serde(default, deserialize_with = "config::item_list")
+    )]
     items: Vec<MenuItem>,
 
     /// The [`MenuOptions`] to use when displaying the menu
+    #[cfg_attr(feature = "serde_serialize", serde(rename = "options", default))]
     configuration: MenuOptions,
+
+    /// Optional callback used to fill the preview pane for the item under the cursor
+    #[cfg_attr(feature = "serde_serialize", serde(skip))]
+    preview: Option<Preview>,
+
+    /// When set, items are drawn as a table using their cells
+    #[cfg_attr(
+        feature = "serde_serialize",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    columns: Option<Vec<Column>>,
+
+    /// Where the selection history is kept, instead of the configured history file
+    #[cfg_attr(feature = "serde_serialize", serde(skip))]
+    history: Option<History>,
+
+    /// Items that are added while the menu is open
+    #[cfg_attr(feature = "serde_serialize", serde(skip))]
+    stream: Option<ItemStream>,
+
+    /// Optional callback that finds the items for the user's search, in place of the menu's own
+    /// searching
+    #[cfg_attr(feature = "serde_serialize", serde(skip))]
+    source: Option<Source>,
+
+    /// The menus that are opened when the items with these ids are picked
+    #[cfg_attr(feature = "serde_serialize", serde(skip))]
+    submenus: HashMap<String, Menu>,
+
+    /// Callbacks that are run as the user interacts with the menu
+    #[cfg_attr(feature = "serde_serialize", serde(skip))]
+    hooks: Hooks,
 }
 
 impl Menu {
@@ -119,8 +438,502 @@ impl Menu {
                     }
                 }
             },
+            preview: None,
+            columns: None,
+            history: None,
+            stream: None,
+            source: None,
+            submenus: HashMap::new(),
+            hooks: Hooks::default(),
+        }
+    }
+
+    /// Check that the menu and its [`MenuOptions`] make sense, returning it if they do. Invalid
+    /// menus fail to [`serve`](Menu::serve) anyways, this catches it sooner
+    pub fn build(self) -> Result<Menu, Error> {
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        self.configuration.validate()?;
+
+        let bad_weight = self.items.iter().find(|item| {
+            let weight = item.search_weight.0;
+            !(weight >= 0.0 && weight.is_finite())
+        });
+        if let Some(item) = bad_weight {
+            return Err(Error::InvalidValue {
+                option: "search_weight",
+                reason: format!(
+                    "the weight of \"{}\" has to be a positive number or 0.0",
+                    item.visible_name
+                ),
+            });
+        }
+
+        // Items that arrive while the menu is open can't be checked ahead of time
+        if let Some(id) = &self.configuration.timeout_default {
+            if self.stream.is_none()
+                && self.source.is_none()
+                && !self.items.iter().any(|item| item.get_id() == id)
+            {
+                return Err(Error::InvalidConfiguration(format!(
+                    "there is no item with the id \"{}\" for the timeout default",
+                    id
+                )));
+            }
+        }
+
+        for submenu in self.submenus.values() {
+            submenu.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Draw the menu's items as a table, using the [`cells`](MenuItem::cells) of each item.
+    /// Items without cells show their name in the first column
+    ///
+    /// Searchable columns can also be searched on their own by starting a search term with the
+    /// column's header and a colon, e.g. typing `region:eu api` only shows items whose "Region"
+    /// cell contains "eu", and then fuzzy matches "api" like normal. Headers are lowercased and
+    /// their spaces are replaced by underscores, so a "Last Seen" column is searched with
+    /// `last_seen:`. See the [crate level docs](crate#searching) for the rest of the syntax
+    ///
+    /// ## Example
+    /// ```
+    /// use cartographer_rs::{menu, menu_item, Column, ColumnAlignment};
+    ///
+    /// let menu = menu!(
+    ///     "Pick a server: ",
+    ///     [
+    ///         menu_item!("api-1").cells(vec!["api-1".into(), "eu".into(), "3d".into()]),
+    ///         menu_item!("db-1").cells(vec!["db-1".into(), "us".into(), "12d".into()])
+    ///     ]
+    /// )
+    /// .columns(vec![
+    ///     Column::new("Name"),
+    ///     Column::new("Region").width(8),
+    ///     Column::new("Age").alignment(ColumnAlignment::Right).searchable(false),
+    /// ]);
+    /// ```
+    pub fn columns(self, columns: Vec<Column>) -> Self {
+        Menu {
+            columns: Some(columns),
+            ..self
+        }
+    }
+
+    /// Show a preview pane for the item under the user's cursor. The callback is given the
+    /// highlighted [`MenuItem`] and returns the text to show, which is re-generated every time the
+    /// cursor lands on a different item.
+    ///
+    /// The preview can be scrolled with `PageUp` and `PageDown`, and its placement is configured
+    /// with [`MenuOptions::preview_position`] and [`MenuOptions::preview_lines`]
+    ///
+    /// The callback is run on the thread that serves the menu while it redraws, so keys
+    /// typed during a slow preview wait for it. Slow previews should be cached, or worked out
+    /// on another thread that the callback checks in on
+    ///
+    /// ## Example
+    /// ```
+    /// use cartographer_rs::{menu, menu_item};
+    ///
+    /// let menu = menu!(
+    ///     "Pick a file: ",
+    ///     [
+    ///         menu_item!("Cargo.toml"),
+    ///         menu_item!("README.org")
+    ///     ]
+    /// )
+    /// .preview(|item| std::fs::read_to_string(item.name()).unwrap_or_default());
+    /// ```
+    pub fn preview<F>(self, callback: F) -> Self
+    where
+        F: Fn(&MenuItem) -> String + Send + Sync + 'static,
+    {
+        Menu {
+            preview: Some(Preview::new(Arc::new(callback))),
+            ..self
+        }
+    }
+
+    /// Open `submenu` in place of this menu when the item with the id `id` is picked with
+    /// `Enter`. Whatever is picked in the submenu is what this menu returns, and closing the
+    /// submenu with `Esc` goes back to this menu. The [`index`](crate::Selected::index) of each
+    /// selected item is where it is in the submenu
+    ///
+    /// ## Example
+    /// ```
+    /// use cartographer_rs::{menu, menu_item};
+    ///
+    /// let environments = menu!("Deploy to: ", [menu_item!("staging"), menu_item!("production")]);
+    /// let menu = menu!("Run: ", [menu_item!("Deploy"), menu_item!("Test")])
+    ///     .submenu("Deploy", environments);
+    /// ```
+    pub fn submenu(self, id: &str, submenu: Menu) -> Self {
+        let mut submenus = self.submenus;
+        submenus.insert(id.to_string(), submenu);
+        Menu { submenus, ..self }
+    }
+}
+
+impl Menu {
+    /// Keep the selection history used for frecency ranking in a custom [`HistoryStore`].
+    /// This takes the place of [`MenuOptions::history_file`]
+    pub fn history(self, store: impl HistoryStore + 'static) -> Self {
+        Menu {
+            history: Some(History::new(Arc::new(store))),
+            ..self
+        }
+    }
+
+    /// Get the history store for this menu, if it has one
+    pub(crate) fn history_store(&self) -> Option<Arc<dyn HistoryStore>> {
+        match (&self.history, &self.configuration.history_file) {
+            (Some(history), _) => Some(history.0.clone()),
+            (None, Some(path)) => Some(Arc::new(FileHistory::new(path))),
+            (None, None) => None,
+        }
+    }
+}
+
+impl Menu {
+    /// Add items to the menu while it is open, as they are sent through `receiver`. The menu is
+    /// shown right away with the items it already has, and search results are updated as new
+    /// items arrive. Until every [`Sender`](std::sync::mpsc::Sender) is dropped, the number of
+    /// items loaded so far is shown below the list.
+    ///
+    /// Items that have arrived are kept, so serving the menu again shows them too.
+    ///
+    /// Showing items as they arrive needs a Unix terminal. Elsewhere, the menu waits for a key
+    /// press as usual, and the items that arrived in the meantime are shown after it.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use cartographer_rs::{Menu, MenuItem};
+    /// use std::sync::mpsc;
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// std::thread::spawn(move || {
+    ///     for entry in std::fs::read_dir(".").unwrap().flatten() {
+    ///         let name = entry.file_name().to_string_lossy().to_string();
+    ///         sender.send(MenuItem::new(name)).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let menu = Menu::new("Pick a file: ".to_string(), vec![], None).stream(receiver);
+    /// let usr_selection = menu.serve().unwrap();
+    /// ```
+    pub fn stream(self, receiver: Receiver<MenuItem>) -> Self {
+        Menu {
+            stream: Some(ItemStream::new(Arc::new(Mutex::new(StreamedItems {
+                receiver: Some(receiver),
+                received: Vec::new(),
+            })))),
+            ..self
         }
     }
+
+    /// Add the items from an iterator while the menu is open, like [`Menu::stream`]. The iterator
+    /// is run on its own thread, starting right away
+    pub fn stream_iter<I>(self, items: I) -> Self
+    where
+        I: IntoIterator<Item = MenuItem> + Send + 'static,
+    {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for item in items {
+                // Stop if the menu is gone
+                if sender.send(item).is_err() {
+                    break;
+                }
+            }
+        });
+        self.stream(receiver)
+    }
+
+    /// Let a callback find the items to show for the user's search, instead of searching the
+    /// menu's items. This is for menus backed by something too big to load all at once, like a
+    /// database or a search index.
+    ///
+    /// The callback is given everything the user has typed, and returns the items to show, in
+    /// the order they are shown. It is called with an empty search when the menu opens (the
+    /// menu's own items are shown until it returns), and again whenever the user stops typing for
+    /// [`MenuOptions::source_debounce`]. It runs on its own thread, so a slow callback doesn't
+    /// hold up typing, and results for a search that has since changed are thrown away.
+    ///
+    /// Selected items stay selected when the items are replaced.
+    ///
+    /// Showing the items as soon as the callback returns needs a Unix terminal. Elsewhere, the
+    /// menu waits for a key press as usual, and the new items are shown after it.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use cartographer_rs::{Menu, MenuItem};
+    ///
+    /// let words = ["apple", "banana", "cherry"];
+    /// let menu = Menu::new("Pick a word: ".to_string(), vec![], None).source(move |query| {
+    ///     words
+    ///         .iter()
+    ///         .filter(|word| word.starts_with(query))
+    ///         .map(|word| MenuItem::new(word.to_string()))
+    ///         .collect()
+    /// });
+    /// let usr_selection = menu.serve().unwrap();
+    /// ```
+    pub fn source<F>(self, callback: F) -> Self
+    where
+        F: Fn(&str) -> Vec<MenuItem> + Send + Sync + 'static,
+    {
+        Menu {
+            source: Some(Source::new(Arc::new(callback))),
+            ..self
+        }
+    }
+}
+
+/// Something the user gave a [`Menu`] that can't be cloned, printed or compared, like a callback.
+/// It is shared between clones of the menu, and only equal to itself, so that [`Menu`] can stay
+/// `Clone`, `Debug` and `PartialEq`
+pub(crate) struct Callback<T: ?Sized>(Arc<T>);
+
+impl<T: ?Sized> Callback<T> {
+    pub(crate) fn new(inner: Arc<T>) -> Self {
+        Callback(inner)
+    }
+}
+
+impl<T: ?Sized> Clone for Callback<T> {
+    fn clone(&self) -> Self {
+        Callback(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> std::fmt::Debug for Callback<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Callback(..)")
+    }
+}
+
+impl<T: ?Sized> PartialEq for Callback<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A custom [`HistoryStore`]
+pub(crate) type History = Callback<dyn HistoryStore>;
+
+/// The user's preview callback
+pub(crate) type Preview = Callback<dyn Fn(&MenuItem) -> String + Send + Sync>;
+
+impl Preview {
+    /// Run the callback for `item`
+    pub(crate) fn generate(&self, item: &MenuItem) -> String {
+        (self.0)(item)
+    }
+}
+
+/// The user's item source callback
+pub(crate) type Source = Callback<dyn Fn(&str) -> Vec<MenuItem> + Send + Sync>;
+
+impl Source {
+    /// Run the callback for `query`
+    pub(crate) fn items(&self, query: &str) -> Vec<MenuItem> {
+        (self.0)(query)
+    }
+}
+
+/// The items being streamed into a [`Menu`]
+pub(crate) type ItemStream = Callback<Mutex<StreamedItems>>;
+
+pub(crate) struct StreamedItems {
+    /// `None` once every sender is gone
+    receiver: Option<Receiver<MenuItem>>,
+    /// Every item that has arrived so far
+    received: Vec<MenuItem>,
+}
+
+impl ItemStream {
+    /// Get the items that have arrived so far
+    pub(crate) fn received(&self) -> Vec<MenuItem> {
+        self.0.lock().unwrap().received.clone()
+    }
+
+    /// Get up to `max` new items, without waiting for any. The second value is `false` once the
+    /// stream has ended and every item has been received
+    pub(crate) fn receive(&self, max: usize) -> (Vec<MenuItem>, bool) {
+        let mut streamed = self.0.lock().unwrap();
+        let mut items = Vec::new();
+        while items.len() < max {
+            let Some(receiver) = &streamed.receiver else {
+                break;
+            };
+            match receiver.try_recv() {
+                Ok(item) => items.push(item),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => streamed.receiver = None,
+            }
+        }
+        streamed.received.extend(items.iter().cloned());
+        (items, streamed.receiver.is_some())
+    }
+}
+
+/// Describes one column of a [`Menu`] that is drawn as a table
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(deny_unknown_fields)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    /// The text shown in the header row
+    header: String,
+
+    /// A fixed width for the column. Cells that are too long are cut short
+    #[cfg_attr(feature = "serde_serialize", serde(default))]
+    width: Option<usize>,
+
+    /// Which side of the column the text sticks to
+    #[cfg_attr(feature = "serde_serialize", serde(default))]
+    alignment: ColumnAlignment,
+
+    /// Whether the cells in this column are used when processing the search results
+    #[cfg_attr(feature = "serde_serialize", serde(default = "config::enabled"))]
+    searchable: bool,
+}
+
+impl Column {
+    /// Create a new left aligned, searchable [`Column`] that is as wide as its widest cell
+    pub fn new(header: &str) -> Self {
+        Column {
+            header: header.to_string(),
+            width: None,
+            alignment: ColumnAlignment::Left,
+            searchable: true,
+        }
+    }
+
+    /// Give the column a fixed width, in terminal columns
+    pub fn width(self, width: usize) -> Self {
+        Column {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    /// Set the text alignment of the column.
+    /// The default is: [`ColumnAlignment::Left`]
+    pub fn alignment(self, alignment: ColumnAlignment) -> Self {
+        Column { alignment, ..self }
+    }
+
+    /// Set if the cells in this column are matched against when searching.
+    /// The default is: true
+    pub fn searchable(self, searchable: bool) -> Self {
+        Column { searchable, ..self }
+    }
+
+    /// The name used to search only this column, e.g. `last_seen:2d` for a "Last Seen" column
+    pub(crate) fn field_name(&self) -> String {
+        self.header
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join("_")
+    }
+}
+
+/// The alignment of the text in a [`Column`]
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColumnAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl From<ColumnAlignment> for console::Alignment {
+    fn from(alignment: ColumnAlignment) -> Self {
+        match alignment {
+            ColumnAlignment::Left => console::Alignment::Left,
+            ColumnAlignment::Center => console::Alignment::Center,
+            ColumnAlignment::Right => console::Alignment::Right,
+        }
+    }
+}
+
+/// Controls whether upper and lower case letters match each other when searching
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseSensitivity {
+    /// Case never matters
+    Insensitive,
+    /// Case always has to match
+    Sensitive,
+    /// Case only has to match if the search contains an upper case letter
+    Smart,
+}
+
+/// Controls how the search scores of an item's name and its alternative matches are combined
+/// into the item's score. See the [crate level docs](crate#ranking) for how the score is used
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScoreAggregation {
+    /// The item scores as well as its best match, whether that is the name or an alternative
+    Max,
+    /// Like [`ScoreAggregation::Max`], but the name's score is multiplied by `label` and the
+    /// alternative matches' scores are multiplied by `alternatives` first. For example,
+    /// `Weighted { label: 2.0, alternatives: 1.0 }` makes the name count double, so items whose
+    /// name matches rank above items that only match through an alternative. The weights also
+    /// apply before the [`minimum_search_threshold`](MenuOptions::minimum_search_threshold) is
+    /// checked
+    Weighted { label: f32, alternatives: f32 },
+}
+
+/// How the items of a menu are arranged on screen
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuLayout {
+    /// One item per line
+    List,
+    /// Items are packed into as many columns as the terminal is wide enough for, and the arrow
+    /// keys move the cursor in two dimensions. Good for long lists of short items
+    Grid,
+}
+
+/// Where the preview pane is drawn in relation to the list of items
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreviewPosition {
+    /// Between the list of items and the prompt
+    Below,
+    /// Beside the list of items. Falls back to [`PreviewPosition::Below`] if the terminal
+    /// is too narrow
+    Right,
 }
 
 /// Controls and characters that can be configured
@@ -129,18 +942,27 @@ impl Menu {
 ///
 /// ## Example
 /// ```
+/// use cartographer_rs::{menu, menu_item, MenuOptions};
+///
 /// let options = MenuOptions::new()
 ///     .cursor("→")
 ///     .select_key(console::Key::Tab)
 ///     .max_lines_visible(6);
 ///
-/// menu!("Only 6 lines are visible!",
-///     menu_items_list,
-///     options
-///     )
-///
+/// let menu = menu!("Only 6 lines are visible!",
+///     options,
+///     [
+///         menu_item!("Item Number 1"),
+///         menu_item!("Item Number 2")
+///     ]
+/// );
 /// ```
 ///
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
 #[derive(Clone, Debug, PartialEq)]
 pub struct MenuOptions {
     /// The user's cursor while they navigate
@@ -152,23 +974,76 @@ pub struct MenuOptions {
     selected_indicator_width: usize,
 
     /// The button the user uses to select an item
+    #[cfg_attr(feature = "serde_serialize", serde(with = "keys::as_name"))]
     select_key: console::Key,
 
     /// The maximum number of vertical lines the menu can have
     max_lines_visible: usize,
 
+    /// How the items are arranged on screen
+    layout: MenuLayout,
+
     ///  The minimum search score for an item to be displayed in the menu
     ///  The lower the number, the more results will be displayed
+    #[cfg_attr(
+        feature = "serde_serialize",
+        serde(rename = "minimum_search_threshold")
+    )]
     min_search_threshold: f32,
 
     /// Configures if selected items stay visible in search results
+    #[cfg_attr(feature = "serde_serialize", serde(rename = "show_selected_in_search"))]
     show_select_in_search: bool,
 
+    /// How the scores of an item's name and alternative matches are combined
+    score_aggregation: ScoreAggregation,
+
+    /// The file selections are recorded in, for frecency ranking
+    history_file: Option<PathBuf>,
+
+    /// How much an item's frecency counts, compared to its search score
+    frecency_weight: f32,
+
+    /// The file that past searches are kept in
+    query_history_file: Option<PathBuf>,
+
+    /// The keys that recall older and newer searches from the query history
+    #[cfg_attr(feature = "serde_serialize", serde(with = "keys::pair_as_names"))]
+    query_history_keys: (console::Key, console::Key),
+
+    /// Whether searches care about upper and lower case
+    case_sensitivity: CaseSensitivity,
+
+    /// Set if accents and other Unicode variations are ignored when searching
+    normalize_unicode: bool,
+
     /// Set if the menu returns the first selected item
+    #[cfg_attr(feature = "serde_serialize", serde(rename = "only_one_selection"))]
     only_one: bool,
 
     /// Set if the menu cleans up the terminal after exiting
+    #[cfg_attr(feature = "serde_serialize", serde(rename = "clear_on_close"))]
     clear_menu_on_exit: bool,
+
+    /// Where the preview pane is drawn, if the menu has one
+    preview_position: PreviewPosition,
+
+    /// The maximum number of lines the preview pane can take up
+    preview_lines: usize,
+
+    /// Set if a menu with columns draws a header row above its items
+    show_column_headers: bool,
+
+    /// How long the user has to stop typing before the menu's item source is asked for items
+    #[cfg_attr(feature = "serde_serialize", serde(with = "config::duration"))]
+    source_debounce: Duration,
+
+    /// How long the menu waits for a key press before giving up
+    #[cfg_attr(feature = "serde_serialize", serde(with = "config::optional_duration"))]
+    timeout: Option<Duration>,
+
+    /// The id of the item that is chosen when the menu times out
+    timeout_default: Option<String>,
 }
 
 impl MenuOptions {
@@ -179,6 +1054,78 @@ impl MenuOptions {
         }
     }
 
+    /// Check that the options make sense, returning them if they do. Menus with invalid options
+    /// fail to [`serve`](Menu::serve) anyways, this catches it sooner
+    ///
+    /// ## Example
+    /// ```
+    /// use cartographer_rs::{Error, MenuOptions};
+    ///
+    /// let opts = MenuOptions::new().minimum_search_threshold(1.5).build();
+    /// assert!(matches!(opts, Err(Error::InvalidValue { .. })));
+    /// ```
+    pub fn build(self) -> Result<Self, Error> {
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let invalid = |option, reason: &str| {
+            Err(Error::InvalidValue {
+                option,
+                reason: reason.to_string(),
+            })
+        };
+
+        if !(0.0..1.0).contains(&self.min_search_threshold) {
+            return invalid(
+                "minimum_search_threshold",
+                "has to be at least 0.0 and less than 1.0",
+            );
+        }
+        if self.max_lines_visible == 0 {
+            return invalid("max_lines_visible", "has to be at least 1");
+        }
+        if !(self.frecency_weight >= 0.0 && self.frecency_weight.is_finite()) {
+            return invalid("frecency_weight", "has to be a positive number or 0.0");
+        }
+        if let ScoreAggregation::Weighted {
+            label,
+            alternatives,
+        } = self.score_aggregation
+        {
+            if !(label >= 0.0
+                && label.is_finite()
+                && alternatives >= 0.0
+                && alternatives.is_finite())
+            {
+                return invalid(
+                    "score_aggregation",
+                    "weights have to be positive numbers or 0.0",
+                );
+            }
+        }
+
+        // These keys would never get to do what they're for
+        let select_key = &self.select_key;
+        if matches!(select_key, console::Key::Enter | console::Key::Escape) {
+            return Err(Error::InvalidConfiguration(format!(
+                "the select key can't be {:?}, which closes the menu",
+                select_key
+            )));
+        }
+        if self.query_history_file.is_some() {
+            let (older, newer) = &self.query_history_keys;
+            if older == newer || older == select_key || newer == select_key {
+                return Err(Error::InvalidConfiguration(
+                    "the query history keys have to be different from each other and the select key"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Set the user's row-indicator/cursor to a custom character.
     /// The default is: '>'
     pub fn cursor(self, cursor: &str) -> Self {
@@ -201,8 +1148,9 @@ impl MenuOptions {
     pub fn selected_indicator_width(self, indicator_width: usize) -> Self {
         MenuOptions { selected_indicator_width: indicator_width, ..self }
     }
-    /// Set the key that is used to select an item.
-    /// The default is: [`console::Key::Char(' ')`]
+    /// Set the key that is used to select an item. When it isn't `Tab`, `Tab` moves the cursor
+    /// to the next item instead.
+    /// The default is: [`console::Key::Tab`], so that spaces can be typed between search terms
     pub fn select_key(self, key: console::Key) -> Self {
         MenuOptions {
             select_key: key,
@@ -217,11 +1165,17 @@ impl MenuOptions {
             ..self
         }
     }
-    /// Set the degree of "fuzziness" that it will match too. Higher numbers will return more
-    /// results, but less accurate ones. Has to be 1.0 >= x >= 0 or will panic
+    /// Set how the items are arranged. In the grid layout, [`max_lines_visible`](MenuOptions::max_lines_visible)
+    /// limits the number of lines, not the number of items.
+    /// The default is: [`MenuLayout::List`]
+    pub fn layout(self, layout: MenuLayout) -> Self {
+        MenuOptions { layout, ..self }
+    }
+    /// Set the degree of "fuzziness" that it will match too. Lower numbers will return more
+    /// results, but less accurate ones. Has to be 1.0 > x >= 0, which is checked by
+    /// [`MenuOptions::build`]
     /// The default is: 0.005
     pub fn minimum_search_threshold(self, threshold: f32) -> Self {
-        assert!( (0.0..1.0).contains(&threshold) );
         MenuOptions {
             min_search_threshold: threshold,
             ..self
@@ -235,7 +1189,77 @@ impl MenuOptions {
             ..self
         }
     }
-    /// Set if the menu should exit and return only the first user selection.
+    /// Set how the search scores of an item's name and its alternative matches are combined.
+    /// The default is: [`ScoreAggregation::Max`]
+    pub fn score_aggregation(self, aggregation: ScoreAggregation) -> Self {
+        MenuOptions {
+            score_aggregation: aggregation,
+            ..self
+        }
+    }
+    /// Record every selection in the file at `path`, and use how frequently and recently each
+    /// item was selected (its "frecency") to rank the items. See the
+    /// [crate level docs](crate#ranking) for how frecency is combined with the search score.
+    /// Use [`Menu::history`] to keep the history somewhere other than a file.
+    /// The default is: no history
+    pub fn history_file(self, path: impl Into<PathBuf>) -> Self {
+        MenuOptions {
+            history_file: Some(path.into()),
+            ..self
+        }
+    }
+    /// Set how much an item's frecency counts, compared to its search score. An item's
+    /// frecency is scaled so that the most frecent item has a frecency of 1.0, then multiplied by
+    /// this weight and added to its score. A weight of 0 only records the history, without
+    /// changing the order of the items.
+    /// The default is: 0.5
+    pub fn frecency_weight(self, weight: f32) -> Self {
+        MenuOptions {
+            frecency_weight: weight,
+            ..self
+        }
+    }
+    /// Remember the searches that were typed into this menu in the file at `path`, so they can be
+    /// recalled with the [`query_history_keys`](MenuOptions::query_history_keys). The last 100
+    /// searches are kept. Use a different file for each menu to keep their searches apart. If the
+    /// file can't be read or written, the menu works as if it had no query history.
+    /// The default is: no query history
+    pub fn query_history_file(self, path: impl Into<PathBuf>) -> Self {
+        MenuOptions {
+            query_history_file: Some(path.into()),
+            ..self
+        }
+    }
+    /// Set the keys that replace the search with an older or newer one from the query history.
+    /// Going newer than the newest search brings back what was being typed before.
+    /// The default is: `Ctrl-R` for older, and `Ctrl-N` for newer
+    pub fn query_history_keys(self, older: console::Key, newer: console::Key) -> Self {
+        MenuOptions {
+            query_history_keys: (older, newer),
+            ..self
+        }
+    }
+    /// Set if searching should care about upper and lower case letters.
+    /// The default is: [`CaseSensitivity::Insensitive`]
+    pub fn case_sensitivity(self, case_sensitivity: CaseSensitivity) -> Self {
+        MenuOptions {
+            case_sensitivity,
+            ..self
+        }
+    }
+    /// Set if searching should ignore accents and other variations of the same character, so
+    /// that "cafe" matches "café", and full-width letters like "ｃａｆｅ" match their usual
+    /// counterparts. This applies Unicode compatibility decomposition (NFKD) to both the search
+    /// and the items, and drops any combining marks.
+    /// The default is: false
+    pub fn normalize_unicode(self, normalize: bool) -> Self {
+        MenuOptions {
+            normalize_unicode: normalize,
+            ..self
+        }
+    }
+    /// Set if the menu should exit and return only the first user selection. Either way,
+    /// pressing enter without selecting anything returns the item under the cursor.
     /// The default is: false
     pub fn only_one_selection(self, only_one: bool) -> Self {
         MenuOptions { only_one, ..self }
@@ -248,6 +1272,66 @@ impl MenuOptions {
             ..self
         }
     }
+    /// Set where the preview pane is drawn. Has no effect unless the menu was given a
+    /// [`Menu::preview`] callback.
+    /// The default is: [`PreviewPosition::Below`]
+    pub fn preview_position(self, position: PreviewPosition) -> Self {
+        MenuOptions {
+            preview_position: position,
+            ..self
+        }
+    }
+    /// Set the maximum height of the preview pane. The pane is shrunk further if the terminal
+    /// doesn't have enough room for it.
+    /// The default is: 8
+    pub fn preview_lines(self, lines: usize) -> Self {
+        MenuOptions {
+            preview_lines: lines,
+            ..self
+        }
+    }
+    /// Set if a menu with [`columns`](Menu::columns) shows the column headers above its items.
+    /// The default is: true
+    pub fn show_column_headers(self, show_headers: bool) -> Self {
+        MenuOptions {
+            show_column_headers: show_headers,
+            ..self
+        }
+    }
+    /// Set how long the user has to stop typing before a menu with a [`Menu::source`] asks it
+    /// for items, so that it isn't asked on every key press.
+    /// The default is: 150ms
+    pub fn source_debounce(self, debounce: Duration) -> Self {
+        MenuOptions {
+            source_debounce: debounce,
+            ..self
+        }
+    }
+    /// Set how long the menu waits for the user before it gives up, counting down in the prompt
+    /// line. The countdown starts over on every key press. When it runs out,
+    /// [`Menu::serve`] returns an [`Error::Timeout`] with the items the user selected, or the
+    /// [`timeout_default`](MenuOptions::timeout_default) if they didn't select any. The search
+    /// and the selected items are saved in the query and selection histories, as they are when
+    /// the user presses enter, but the default isn't, since the user didn't pick it.
+    ///
+    /// The countdown needs a Unix terminal, elsewhere the menu waits for a key press as usual.
+    /// The default is: None
+    pub fn timeout(self, timeout: Duration) -> Self {
+        MenuOptions {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+    /// Set the [`id`](MenuItem::get_id) of the item that is chosen if the menu times out without
+    /// a selection, and shown in the countdown. Has no effect without a
+    /// [`timeout`](MenuOptions::timeout).
+    /// The default is: None
+    pub fn timeout_default(self, id: impl Into<String>) -> Self {
+        MenuOptions {
+            timeout_default: Some(id.into()),
+            ..self
+        }
+    }
 }
 
 impl Default for MenuOptions {
@@ -257,12 +1341,55 @@ impl Default for MenuOptions {
             cursor_width: 1,
             selected_indicator: "X".to_string(),
             selected_indicator_width: 1,
-            select_key: console::Key::Char(' '),
+            select_key: console::Key::Tab,
             max_lines_visible: 10,
+            layout: MenuLayout::List,
             min_search_threshold: 0.005,
             show_select_in_search: true,
+            score_aggregation: ScoreAggregation::Max,
+            history_file: None,
+            frecency_weight: 0.5,
+            query_history_file: None,
+            query_history_keys: (console::Key::Char('\x12'), console::Key::Char('\x0e')),
+            case_sensitivity: CaseSensitivity::Insensitive,
+            normalize_unicode: false,
             only_one: false,
             clear_menu_on_exit: true,
+            preview_position: PreviewPosition::Below,
+            preview_lines: 8,
+            show_column_headers: true,
+            source_debounce: Duration::from_millis(150),
+            timeout: None,
+            timeout_default: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    #[test]
+    fn items_are_compared_without_their_values() {
+        let hash = |item: &MenuItem| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            hasher.finish()
+        };
+        let plain = MenuItem::new("Deploy".to_string());
+        let first = plain.clone().value(1);
+        let second = plain.clone().value("two");
+
+        assert_eq!(first, second);
+        assert_eq!(first.cmp(&second), std::cmp::Ordering::Equal);
+        assert_eq!(hash(&first), hash(&second));
+        assert_eq!(hash(&first), hash(&plain));
+
+        // Everything else still counts
+        let other = plain.clone().description("Ship it".to_string());
+        assert_ne!(plain, other);
+        assert!(plain < other);
+    }
+}
@@ -0,0 +1,294 @@
+use super::{Menu, MenuItem};
+use crate::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::path::Path;
+use std::time::Duration;
+
+impl Menu {
+    /// Load a menu from a TOML, YAML or JSON file, picked by the file's extension (`.toml`,
+    /// `.yaml` or `.yml`, and `.json`). Each format needs the crate feature of the same name.
+    /// The menu is checked with [`Menu::build`] before it is returned.
+    ///
+    /// The file has a `prompt`, a list of `items`, and optionally `options` and `columns`. Items
+    /// can be written as just their name, or with the same fields as a [`MenuItem`]. The
+    /// `options` are named after the [`MenuOptions`](crate::MenuOptions) builder methods,
+    /// keys are written as a [`KeyName`](crate::KeyName), and durations as milliseconds or with
+    /// a unit, like `"150ms"` or `"5s"`. Anything left out keeps its default, and fields that
+    /// don't exist are an error, so that a misspelled option isn't silently ignored.
+    ///
+    /// ## Example
+    /// ```toml
+    /// prompt = "Deploy to: "
+    /// items = [
+    ///     "staging",
+    ///     { visible_name = "production", alternative_matches = ["prod"] },
+    /// ]
+    ///
+    /// [options]
+    /// select_key = "tab"
+    /// timeout = "30s"
+    /// timeout_default = "staging"
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Menu, Error> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        let text = std::fs::read_to_string(path)?;
+
+        let menu = match extension.as_deref() {
+            Some("toml") => Menu::from_toml(&text),
+            Some("yaml" | "yml") => Menu::from_yaml(&text),
+            Some("json") => Menu::from_json(&text),
+            _ => Err(Error::Parse(
+                "expected a .toml, .yaml, .yml or .json file".to_string(),
+            )),
+        };
+        menu.map_err(|err| match err {
+            Error::Parse(reason) => Error::Parse(format!("{}: {}", path.display(), reason)),
+            err => err,
+        })
+    }
+
+    /// Load a menu from TOML, laid out like it is for [`Menu::from_file`]
+    pub fn from_toml(text: &str) -> Result<Menu, Error> {
+        #[cfg(feature = "toml")]
+        let menu: Result<Menu, Error> =
+            toml::from_str(text).map_err(|err| Error::Parse(err.to_string()));
+        #[cfg(not(feature = "toml"))]
+        let menu: Result<Menu, Error> = missing_feature(text, "toml");
+        menu?.build()
+    }
+
+    /// Load a menu from YAML, laid out like it is for [`Menu::from_file`]
+    pub fn from_yaml(text: &str) -> Result<Menu, Error> {
+        #[cfg(feature = "yaml")]
+        let menu: Result<Menu, Error> =
+            serde_yaml::from_str(text).map_err(|err| Error::Parse(err.to_string()));
+        #[cfg(not(feature = "yaml"))]
+        let menu: Result<Menu, Error> = missing_feature(text, "yaml");
+        menu?.build()
+    }
+
+    /// Load a menu from JSON, laid out like it is for [`Menu::from_file`]
+    pub fn from_json(text: &str) -> Result<Menu, Error> {
+        #[cfg(feature = "json")]
+        let menu: Result<Menu, Error> =
+            serde_json::from_str(text).map_err(|err| Error::Parse(err.to_string()));
+        #[cfg(not(feature = "json"))]
+        let menu: Result<Menu, Error> = missing_feature(text, "json");
+        menu?.build()
+    }
+}
+
+/// The error for a format whose crate feature isn't turned on
+#[cfg(not(all(feature = "toml", feature = "yaml", feature = "json")))]
+fn missing_feature(_text: &str, format: &str) -> Result<Menu, Error> {
+    Err(Error::Parse(format!(
+        "reading {} needs the `{}` feature of cartographer-rs",
+        format.to_uppercase(),
+        format
+    )))
+}
+
+/// The default for boolean fields that are on unless they are turned off
+pub(super) fn enabled() -> bool {
+    true
+}
+
+/// Reads a list of items, where each item is either its name or a full [`MenuItem`]
+pub(super) fn item_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<MenuItem>, D::Error> {
+    // Not an untagged enum, so that a mistake in an item is reported as it is, instead of as an
+    // item that matches neither form
+    struct Entry(MenuItem);
+
+    impl<'de> Deserialize<'de> for Entry {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(EntryVisitor)
+        }
+    }
+
+    struct EntryVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for EntryVisitor {
+        type Value = Entry;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("an item's name, or a table of its fields")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Entry, E> {
+            Ok(Entry(MenuItem::new(name.to_string())))
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Entry, A::Error> {
+            let item = MenuItem::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+            Ok(Entry(item))
+        }
+    }
+
+    Ok(Vec::<Entry>::deserialize(deserializer)?
+        .into_iter()
+        .map(|Entry(item)| item)
+        .collect())
+}
+
+/// (De)serializes a [`Duration`] as text like `150ms` or `5s`. Plain numbers are read as
+/// milliseconds
+pub(super) mod duration {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let millis = duration.as_millis();
+        if millis.is_multiple_of(1000) {
+            serializer.serialize_str(&format!("{}s", millis / 1000))
+        } else {
+            serializer.serialize_str(&format!("{}ms", millis))
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Duration, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Written {
+            Millis(u64),
+            Text(String),
+        }
+
+        match Written::deserialize(deserializer)? {
+            Written::Millis(millis) => Ok(Duration::from_millis(millis)),
+            Written::Text(text) => parse(&text).ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "`{}` isn't a duration, expected something like \"150ms\", \"5s\" or \"2m\"",
+                    text
+                ))
+            }),
+        }
+    }
+
+    fn parse(text: &str) -> Option<Duration> {
+        let text = text.trim();
+        let split = text
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let seconds = match unit.trim() {
+            "ms" | "" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 60.0 * 60.0,
+            _ => return None,
+        };
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+/// Like [`duration`], for a [`Duration`] that is optional
+pub(super) mod optional_duration {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::duration::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapper(#[serde(deserialize_with = "super::duration::deserialize")] Duration);
+
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(duration)| duration))
+    }
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod tests {
+    use super::*;
+    use crate::{menu_item, MenuOptions};
+
+    /// The TOML example in the README
+    fn readme_example() -> &'static str {
+        let readme = include_str!("../../README.org");
+        let start = readme.find("#+begin_src toml\n").unwrap() + "#+begin_src toml\n".len();
+        let end = start + readme[start..].find("#+end_src").unwrap();
+        &readme[start..end]
+    }
+
+    #[test]
+    fn examples_load() {
+        let options = MenuOptions::new()
+            .select_key(console::Key::Tab)
+            .only_one_selection(true);
+        let expected = Menu::new(
+            "Deploy to: ".to_string(),
+            vec![
+                menu_item!("staging"),
+                menu_item!("production", aliases = ["prod"]),
+            ],
+            Some(options.clone()),
+        );
+        assert_eq!(Menu::from_toml(readme_example()).unwrap(), expected);
+
+        let expected = Menu::new(
+            "Deploy to: ".to_string(),
+            vec![
+                menu_item!("staging"),
+                menu_item!("production", aliases = ["prod", "live"]),
+                menu_item!("local", hidden),
+            ],
+            Some(options.cursor("➤").max_lines_visible(5)),
+        );
+        assert_eq!(
+            Menu::from_file("examples/config_file.toml").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        let error = |text: &str| match Menu::from_toml(text) {
+            Err(Error::Parse(reason)) => reason,
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+
+        let reason = error("prompt = \"> \"\nitems = []\ntitle = \"Deploy\"");
+        assert!(reason.contains("unknown field `title`"), "{}", reason);
+        let reason = error("prompt = \"> \"\nitems = []\n[options]\nselect = \"tab\"");
+        assert!(reason.contains("unknown field `select`"), "{}", reason);
+        let reason = error("prompt = \"> \"\nitems = [{ visible_name = \"a\", aliases = [] }]");
+        assert!(reason.contains("unknown field `aliases`"), "{}", reason);
+        let reason = error("prompt = \"> \"\nitems = []\n[options]\nselect_key = \"hyper-x\"");
+        assert!(reason.contains("hyper-x"), "{}", reason);
+        let reason = error("prompt = \"> \"\nitems = []\n[options]\ntimeout = \"soon\"");
+        assert!(reason.contains("isn't a duration"), "{}", reason);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        let menu = Menu::from_toml(readme_example()).unwrap();
+        let json = serde_json::to_string(&menu).unwrap();
+        assert_eq!(Menu::from_json(&json).unwrap(), menu);
+
+        let json = r#"{"prompt": "> ", "items": ["a", {"visible_name": "b", "hidden": true}]}"#;
+        assert!(matches!(
+            Menu::from_json(json),
+            Err(Error::Parse(reason)) if reason.contains("unknown field `hidden`")
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use super::{Menu, MenuItem, MenuOptions};
+use std::cmp::Ordering;
+
+/// Changing a menu in place, so that a menu that is kept around, like one that is served over
+/// and over in a loop, can be updated between calls to [`Menu::serve`].
+///
+/// Items are found by their [`id`](MenuItem::get_id). These only change the items the menu was
+/// given, not ones that were [streamed](Menu::stream) in or came from a [`source`](Menu::source)
+///
+/// ## Example
+/// ```no_run
+/// use cartographer_rs::{menu, menu_item};
+///
+/// let mut menu = menu!("Open: ", [menu_item!("New file")]);
+/// while let Some(selection) = menu.serve().unwrap() {
+///     for name in selection {
+///         if name == "New file" {
+///             let count = menu.get_items().len();
+///             menu.push_item(menu_item!(format!("untitled-{}", count)));
+///         } else {
+///             // Recently opened files go to the top
+///             menu.move_item(&name, 1);
+///         }
+///     }
+///     menu.set_prompt(format!("Open ({} files): ", menu.get_items().len() - 1));
+/// }
+/// ```
+impl Menu {
+    /// Get the text shown in front of the user's input
+    pub fn get_prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    /// Change the text shown in front of the user's input
+    pub fn set_prompt(&mut self, prompt: String) {
+        self.prompt = prompt;
+    }
+
+    /// Get the [`MenuOptions`] the menu is shown with
+    pub fn get_options(&self) -> &MenuOptions {
+        &self.configuration
+    }
+
+    /// Replace the [`MenuOptions`] the menu is shown with
+    pub fn set_options(&mut self, configuration: MenuOptions) {
+        self.configuration = configuration;
+    }
+
+    /// Get the menu's items, in the order they were given
+    pub fn get_items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    /// Add an item to the end of the menu
+    pub fn push_item(&mut self, item: MenuItem) {
+        self.items.push(item);
+    }
+
+    /// Add an item at `index`, moving the items after it down
+    ///
+    /// ## Panics
+    /// If `index` is greater than the number of items
+    pub fn insert_item(&mut self, index: usize, item: MenuItem) {
+        self.items.insert(index, item);
+    }
+
+    /// Take the first item with the id `id` out of the menu, returning it if there was one
+    pub fn remove_item(&mut self, id: &str) -> Option<MenuItem> {
+        let index = self.item_index(id)?;
+        Some(self.items.remove(index))
+    }
+
+    /// Only keep the items that `keep` returns `true` for
+    pub fn retain_items(&mut self, keep: impl FnMut(&MenuItem) -> bool) {
+        self.items.retain(keep);
+    }
+
+    /// Take all of the items out of the menu
+    pub fn clear_items(&mut self) {
+        self.items.clear();
+    }
+
+    /// Change the first item with the id `id`, with the same builder methods it was made with.
+    /// Returns `false` if there isn't an item with that id
+    ///
+    /// ## Example
+    /// ```
+    /// use cartographer_rs::{menu, menu_item};
+    ///
+    /// let mut menu = menu!("Pick: ", [menu_item!("draft"), menu_item!("final")]);
+    /// assert!(menu.update_item("draft", |item| item.visible_at_rest(false)));
+    /// assert!(!menu.update_item("missing", |item| item.pinned(true)));
+    /// ```
+    pub fn update_item(&mut self, id: &str, change: impl FnOnce(MenuItem) -> MenuItem) -> bool {
+        let Some(index) = self.item_index(id) else {
+            return false;
+        };
+        // Swap in a placeholder while the item is moved through `change`
+        let item = std::mem::replace(&mut self.items[index], MenuItem::new(String::new()));
+        self.items[index] = change(item);
+        true
+    }
+
+    /// Move the first item with the id `id` to `index`, or to the end if `index` is past it.
+    /// Returns `false` if there isn't an item with that id
+    pub fn move_item(&mut self, id: &str, index: usize) -> bool {
+        let Some(from) = self.item_index(id) else {
+            return false;
+        };
+        let item = self.items.remove(from);
+        self.items.insert(index.min(self.items.len()), item);
+        true
+    }
+
+    /// Sort the items with a comparison function. The sort is stable, so equal items keep their
+    /// order. Items with an [`at_rest_position`](MenuItem::at_rest_position) are still shown
+    /// in that order at rest
+    pub fn sort_items_by(&mut self, compare: impl FnMut(&MenuItem, &MenuItem) -> Ordering) {
+        self.items.sort_by(compare);
+    }
+
+    /// Find where the first item with the id `id` is
+    fn item_index(&self, id: &str) -> Option<usize> {
+        self.items.iter().position(|item| item.get_id() == id)
+    }
+}
@@ -0,0 +1,157 @@
+use super::{Callback, Menu, MenuItem};
+use std::sync::Arc;
+
+/// What happened in a menu, passed to the hooks set with [`Menu::on_cursor_move`],
+/// [`Menu::on_toggle`], [`Menu::on_query_change`] and [`Menu::on_submit`].
+///
+/// Hooks run before the menu acts on the key press, so they can [`veto`](MenuEvent::veto) it.
+/// They can also change the menu's items and prompt, which is shown once the hook returns
+///
+/// ## Example
+/// ```no_run
+/// use cartographer_rs::{menu, menu_item};
+///
+/// let menu = menu!(
+///     "Files: ",
+///     [menu_item!("notes.txt"), menu_item!("report.pdf"), menu_item!("locked.db")]
+/// )
+/// // Keep a status line in the prompt
+/// .on_toggle(|event| {
+///     if event.item().is_some_and(|item| item.name() == "locked.db") {
+///         event.veto();
+///         return;
+///     }
+///     let count = event.selected().len();
+///     let count = match event.item().is_some_and(|item| event.selected().contains(item)) {
+///         true => count - 1,
+///         false => count + 1,
+///     };
+///     event.set_prompt(format!("Files ({} selected): ", count));
+/// })
+/// // Don't close the menu with nothing picked
+/// .on_submit(|event| {
+///     if event.selected().is_empty() {
+///         event.veto();
+///     }
+/// });
+/// ```
+#[derive(Debug)]
+pub struct MenuEvent {
+    pub(super) item: Option<MenuItem>,
+    pub(super) query: String,
+    pub(super) selected: Vec<MenuItem>,
+    pub(super) vetoed: bool,
+    pub(super) replaced: Vec<MenuItem>,
+    pub(super) added: Vec<MenuItem>,
+    pub(super) prompt: Option<String>,
+}
+
+impl MenuEvent {
+    /// The item the event is about: the item the cursor is moving to, the item being selected
+    /// or unselected, or the item under the cursor for the other events
+    pub fn item(&self) -> Option<&MenuItem> {
+        self.item.as_ref()
+    }
+
+    /// The search, as it will be if a query change isn't vetoed
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The items that are selected, before the event. When the menu is submitted, this is what
+    /// it returns
+    pub fn selected(&self) -> &[MenuItem] {
+        &self.selected
+    }
+
+    /// Stop the menu from acting on the key press: the cursor doesn't move, the item isn't
+    /// selected or unselected, the search doesn't change, or the menu isn't closed
+    pub fn veto(&mut self) {
+        self.vetoed = true;
+    }
+
+    /// Replace the menu's item that has the same [`id`](MenuItem::get_id) as `item`, like to
+    /// give it a [`description`](MenuItem::description) once its details are loaded
+    pub fn replace_item(&mut self, item: MenuItem) {
+        self.replaced.push(item);
+    }
+
+    /// Add an item to the menu
+    pub fn push_item(&mut self, item: MenuItem) {
+        self.added.push(item);
+    }
+
+    /// Change the text shown in front of the user's input
+    pub fn set_prompt(&mut self, prompt: String) {
+        self.prompt = Some(prompt);
+    }
+}
+
+/// One of the user's event hooks
+pub(crate) type Hook = Callback<dyn Fn(&mut MenuEvent) + Send + Sync>;
+
+impl Hook {
+    /// Run the hook for `event`
+    pub(crate) fn call(&self, event: &mut MenuEvent) {
+        (self.0)(event)
+    }
+}
+
+/// The hooks a menu runs as the user interacts with it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Hooks {
+    pub(crate) cursor_move: Option<Hook>,
+    pub(crate) toggle: Option<Hook>,
+    pub(crate) query_change: Option<Hook>,
+    pub(crate) submit: Option<Hook>,
+}
+
+impl Menu {
+    /// Run `hook` when the user moves the cursor with the arrow keys, or `Tab` if it isn't the
+    /// select key, before it moves. The event's [`item`](MenuEvent::item) is the item the cursor
+    /// is moving to
+    ///
+    /// Hooks are run on the thread that serves the menu, and the menu waits for them, so
+    /// anything slow should be handed off to another thread
+    pub fn on_cursor_move<F>(self, hook: F) -> Self
+    where
+        F: Fn(&mut MenuEvent) + Send + Sync + 'static,
+    {
+        let mut hooks = self.hooks;
+        hooks.cursor_move = Some(Hook::new(Arc::new(hook)));
+        Menu { hooks, ..self }
+    }
+
+    /// Run `hook` when the user presses the select key, before the item under the cursor is
+    /// selected or unselected
+    pub fn on_toggle<F>(self, hook: F) -> Self
+    where
+        F: Fn(&mut MenuEvent) + Send + Sync + 'static,
+    {
+        let mut hooks = self.hooks;
+        hooks.toggle = Some(Hook::new(Arc::new(hook)));
+        Menu { hooks, ..self }
+    }
+
+    /// Run `hook` when the user changes the search, by typing or recalling a past search,
+    /// before the new search is run. The event's [`query`](MenuEvent::query) is the new search
+    pub fn on_query_change<F>(self, hook: F) -> Self
+    where
+        F: Fn(&mut MenuEvent) + Send + Sync + 'static,
+    {
+        let mut hooks = self.hooks;
+        hooks.query_change = Some(Hook::new(Arc::new(hook)));
+        Menu { hooks, ..self }
+    }
+
+    /// Run `hook` when the user presses `Enter` to close the menu with their selection, before
+    /// it closes. Opening a [`submenu`](Menu::submenu) doesn't run it
+    pub fn on_submit<F>(self, hook: F) -> Self
+    where
+        F: Fn(&mut MenuEvent) + Send + Sync + 'static,
+    {
+        let mut hooks = self.hooks;
+        hooks.submit = Some(Hook::new(Arc::new(hook)));
+        Menu { hooks, ..self }
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How often, and how recently, an item was selected
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The [`id`](crate::MenuItem::id) of the selected item
+    pub id: String,
+    /// The number of times the item has been selected
+    pub count: u32,
+    /// When the item was last selected, in seconds since the Unix epoch
+    pub last_used: u64,
+}
+
+impl HistoryEntry {
+    /// Combine how often and how recently the item was selected into one number. Every
+    /// selection counts 4 times within the first hour, 2 times within the first day, 0.5 times
+    /// within the first week, and 0.25 times after that
+    pub fn frecency(&self, now: u64) -> f32 {
+        const HOUR: u64 = 60 * 60;
+        const DAY: u64 = HOUR * 24;
+        const WEEK: u64 = DAY * 7;
+
+        let recency = match now.saturating_sub(self.last_used) {
+            age if age < HOUR => 4.0,
+            age if age < DAY => 2.0,
+            age if age < WEEK => 0.5,
+            _ => 0.25,
+        };
+        self.count as f32 * recency
+    }
+}
+
+/// Somewhere to keep the selection history that frecency ranking is based on.
+///
+/// [`FileHistory`] is used when [`MenuOptions::history_file`](crate::MenuOptions::history_file)
+/// is set. Implement this trait and pass it to [`Menu::history`](crate::Menu::history) to keep the
+/// history somewhere else. Both methods take `&self`, so stores that keep the history in memory
+/// need to use interior mutability.
+///
+/// The history only affects the ranking, so errors from either method are ignored: the menu is
+/// served without frecency if the history can't be loaded, and the selection is still returned if
+/// it can't be recorded.
+pub trait HistoryStore: Send + Sync {
+    /// Get every item that has been selected before
+    fn load(&self) -> std::io::Result<Vec<HistoryEntry>>;
+
+    /// Record that the items with these ids were just selected
+    fn record(&self, ids: &[&str]) -> std::io::Result<()>;
+}
+
+/// A [`HistoryStore`] that keeps the history in a plain text file, one item per line.
+///
+/// The file is replaced as a whole on every selection, so it is never left half written. There is
+/// no locking though, so if two menus using the same file close at the same moment, one of their
+/// selections may not be counted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileHistory {
+    path: PathBuf,
+}
+
+impl FileHistory {
+    /// Use the file at `path`. It is created on the first selection if it doesn't exist yet
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileHistory { path: path.into() }
+    }
+}
+
+impl HistoryStore for FileHistory {
+    fn load(&self) -> std::io::Result<Vec<HistoryEntry>> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            // Nothing has been selected yet
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        // Every line is "count\tlast_used\tid". Skip any that aren't, rather than failing the menu
+        Ok(contents
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                Some(HistoryEntry {
+                    count: parts.next()?.parse().ok()?,
+                    last_used: parts.next()?.parse().ok()?,
+                    id: unescape(parts.next()?),
+                })
+            })
+            .collect())
+    }
+
+    fn record(&self, ids: &[&str]) -> std::io::Result<()> {
+        let now = now();
+        let mut entries = self.load()?;
+        for id in ids {
+            match entries.iter_mut().find(|entry| entry.id == *id) {
+                Some(entry) => {
+                    entry.count = entry.count.saturating_add(1);
+                    entry.last_used = now;
+                }
+                None => entries.push(HistoryEntry {
+                    id: id.to_string(),
+                    count: 1,
+                    last_used: now,
+                }),
+            }
+        }
+
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}\t{}\t{}",
+                    entry.count,
+                    entry.last_used,
+                    escape(&entry.id)
+                )
+            })
+            .collect();
+        write_lines(&self.path, &lines)
+    }
+}
+
+/// The most searches that are kept in a query history file
+const QUERY_HISTORY_LIMIT: usize = 100;
+
+/// Load the searches in a query history file, oldest first
+pub(crate) fn load_queries(path: &Path) -> std::io::Result<Vec<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(contents.lines().map(unescape).collect()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Add a search to the end of a query history file, dropping the oldest searches if the file is
+/// full. A search that is already in the history is moved to the end instead of repeated
+pub(crate) fn save_query(path: &Path, query: &str) -> std::io::Result<()> {
+    let mut queries = load_queries(path)?;
+    queries.retain(|old_query| old_query != query);
+    queries.push(query.to_string());
+
+    let start = queries.len().saturating_sub(QUERY_HISTORY_LIMIT);
+    let lines: Vec<String> = queries[start..].iter().map(|query| escape(query)).collect();
+    write_lines(path, &lines)
+}
+
+/// Replace the file's contents with `lines`
+fn write_lines(path: &Path, lines: &[String]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write to a temporary file first, so that a crash can't leave half of a history behind. Every
+    // write gets its own, so that menus closing at the same time don't write into the same file
+    let tmp_path = tmp_path(path);
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .and_then(|mut file| {
+            for line in lines {
+                writeln!(file, "{}", line)?;
+            }
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&tmp_path, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    written
+}
+
+/// A temporary file next to `path` that no other process or thread will pick, like
+/// `.history.1234.5f3a09c1d2e4b876.tmp`
+fn tmp_path(path: &Path) -> PathBuf {
+    // RandomState is seeded randomly, which saves pulling in a crate just for this
+    let suffix = RandomState::new().build_hasher().finish();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(
+        ".{}.{}.{:016x}.tmp",
+        name,
+        std::process::id(),
+        suffix
+    ))
+}
+
+/// The current time in seconds since the Unix epoch
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+/// Keep ids and queries on a single line
+fn escape(id: &str) -> String {
+    id.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t")
+}
+
+fn unescape(id: &str) -> String {
+    let mut output = String::new();
+    let mut chars = id.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => output.push('\n'),
+                Some('r') => output.push('\r'),
+                Some('t') => output.push('\t'),
+                Some(other) => output.push(other),
+                None => output.push('\\'),
+            }
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_history_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cartographer-history-{}", std::process::id()));
+        let history = FileHistory::new(dir.join("history"));
+
+        assert_eq!(history.load().unwrap(), Vec::new());
+        history.record(&["a", "tab\there"]).unwrap();
+        history.record(&["a"]).unwrap();
+
+        let entries = history.load().unwrap();
+        let counts: Vec<(&str, u32)> = entries
+            .iter()
+            .map(|entry| (entry.id.as_str(), entry.count))
+            .collect();
+        assert_eq!(counts, [("a", 2), ("tab\there", 1)]);
+
+        // Only the history itself is left, no temporary files
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn query_history_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cartographer-queries-{}", std::process::id()));
+        let path = dir.join("queries");
+
+        assert_eq!(load_queries(&path).unwrap(), Vec::<String>::new());
+        let awkward = [
+            "two\nlines",
+            "carriage return\r",
+            "tab\there",
+            "back\\slash\\n",
+            "é",
+        ];
+        for query in awkward {
+            save_query(&path, query).unwrap();
+        }
+        assert_eq!(load_queries(&path).unwrap(), awkward);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 5);
+
+        // Searching for something again moves it to the end
+        save_query(&path, "tab\there").unwrap();
+        assert_eq!(
+            load_queries(&path).unwrap(),
+            [
+                "two\nlines",
+                "carriage return\r",
+                "back\\slash\\n",
+                "é",
+                "tab\there"
+            ]
+        );
+
+        // Only the latest searches are kept
+        for i in 0..QUERY_HISTORY_LIMIT {
+            save_query(&path, &i.to_string()).unwrap();
+        }
+        let queries = load_queries(&path).unwrap();
+        assert_eq!(queries.len(), QUERY_HISTORY_LIMIT);
+        assert_eq!(queries[0], "0");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn tmp_paths_are_unique() {
+        let path = Path::new("/some/dir/history");
+        let (first, second) = (tmp_path(path), tmp_path(path));
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), path.parent());
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+/// Keeps the terminal from echoing and line buffering key presses while a menu is open.
+///
+/// [`Term::read_key`](console::Term::read_key) only puts the terminal in raw mode while it is
+/// waiting for a key, so anything typed while the menu is busy (like searching a long list) would
+/// otherwise be drawn over the menu, and couldn't be seen until the user pressed enter. With this
+/// in place, [`InputMode::key_pending`] can tell when the user has typed something, so that the
+/// work can be cut short. The terminal is put back the way it was when this is dropped.
+pub(crate) struct InputMode {
+    #[cfg(unix)]
+    inner: Option<unix::RawInput>,
+}
+
+impl InputMode {
+    pub(crate) fn new() -> std::io::Result<InputMode> {
+        Ok(InputMode {
+            #[cfg(unix)]
+            inner: unix::RawInput::new()?,
+        })
+    }
+
+    /// Check if there is a key press waiting to be read, without waiting for one
+    pub(crate) fn key_pending(&self) -> bool {
+        #[cfg(unix)]
+        if let Some(inner) = &self.inner {
+            return inner.poll(Duration::ZERO);
+        }
+        false
+    }
+
+    /// Wait for a key press with [`Term::read_key`](console::Term::read_key). The terminal is put
+    /// back the way it was while waiting, because on Ctrl-C, console raises SIGINT right after
+    /// putting back whatever mode it found the terminal in, and the program would exit without
+    /// echo
+    pub(crate) fn read_key(&self, term: &console::Term) -> std::io::Result<console::Key> {
+        #[cfg(unix)]
+        if let Some(inner) = &self.inner {
+            inner.restore()?;
+            let key = term.read_key();
+            inner.apply()?;
+            return key;
+        }
+        term.read_key()
+    }
+
+    /// Wait up to `timeout` for a key press. Returns `true` if there is one to read.
+    ///
+    /// Where it isn't possible to tell, like on platforms other than Unix, this returns `true`
+    /// right away, so that the caller goes on to wait for the key with
+    /// [`Term::read_key`](console::Term::read_key)
+    pub(crate) fn wait_for_key(&self, timeout: Duration) -> bool {
+        #[cfg(unix)]
+        if let Some(inner) = &self.inner {
+            return inner.poll(timeout);
+        }
+        let _ = timeout;
+        true
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs::File;
+    use std::os::unix::io::{AsRawFd, RawFd};
+    use std::time::Duration;
+
+    pub(super) struct RawInput {
+        fd: RawFd,
+        original: libc::termios,
+        raw: libc::termios,
+        // Kept open for as long as `fd` is used
+        _tty: Option<File>,
+    }
+
+    impl RawInput {
+        /// Returns `None` if there isn't a terminal to read from
+        pub(super) fn new() -> std::io::Result<Option<RawInput>> {
+            // Read from the same place console does
+            let (fd, tty) = if unsafe { libc::isatty(libc::STDIN_FILENO) } == 1 {
+                (libc::STDIN_FILENO, None)
+            } else {
+                match File::open("/dev/tty") {
+                    Ok(tty) => (tty.as_raw_fd(), Some(tty)),
+                    Err(_) => return Ok(None),
+                }
+            };
+
+            let mut termios = std::mem::MaybeUninit::uninit();
+            if unsafe { libc::tcgetattr(fd, termios.as_mut_ptr()) } != 0 {
+                return Ok(None);
+            }
+            let original = unsafe { termios.assume_init() };
+
+            // Ctrl-C is read like any other key until the menu waits for one, so that the
+            // terminal can always be put back
+            let mut termios = original;
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            let input = RawInput {
+                fd,
+                original,
+                raw: termios,
+                _tty: tty,
+            };
+            input.apply()?;
+            Ok(Some(input))
+        }
+
+        /// Switch to the menu's mode
+        pub(super) fn apply(&self) -> std::io::Result<()> {
+            self.set(&self.raw)
+        }
+
+        /// Switch back to the mode the terminal was in before the menu
+        pub(super) fn restore(&self) -> std::io::Result<()> {
+            self.set(&self.original)
+        }
+
+        fn set(&self, termios: &libc::termios) -> std::io::Result<()> {
+            if unsafe { libc::tcsetattr(self.fd, libc::TCSADRAIN, termios) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        /// Wait up to `timeout` for there to be something to read
+        pub(super) fn poll(&self, timeout: Duration) -> bool {
+            let mut pollfd = libc::pollfd {
+                fd: self.fd,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+            unsafe { libc::poll(&mut pollfd, 1, timeout) > 0 }
+        }
+    }
+
+    impl Drop for RawInput {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+}
@@ -1,15 +1,83 @@
+use super::events::Hook;
+use super::input::InputMode;
+use super::query::{PastSearches, Query, SearchKeys};
+use super::ItemStream;
+use super::Preview;
+use super::Source;
+use crate::Column;
+use crate::Error;
+use crate::HistoryStore;
 use crate::Menu;
+use crate::MenuEvent;
 use crate::MenuItem;
+use crate::MenuLayout;
 use crate::MenuOptions;
+use crate::MenuSession;
+use crate::PreviewPosition;
+use crate::Selected;
 use console::Key;
 use console::Term;
-use rust_fuzzy_search::fuzzy_compare;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The number of spaces between columns in the grid layout
+const GRID_GAP: usize = 2;
+
+/// The spacing between the columns of a table
+const COLUMN_GAP: &str = "  ";
+
+/// How many rows are searched between checks for new key presses
+const SEARCH_CHUNK: usize = 4096;
+
+/// How often the results of a search that is still running are drawn
+const SEARCH_REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The most streamed items that are added to the menu at once
+const STREAM_BATCH: usize = 10_000;
+
+/// How long to wait for a key press before checking for more streamed items, or results from
+/// the item source
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 struct MenuItemKeepTrack {
     menu_item: MenuItem,
+    // Where the item is in the list it came from
+    index: usize,
+    // The item's text, ready to be searched
+    keys: SearchKeys,
     is_visible: bool,
     is_selected: bool,
+    // The score from the last search
+    score: f32,
+    // How frequently and recently the item was selected, from 0 to 1
+    frecency: f32,
+}
+
+impl MenuItemKeepTrack {
+    /// The score from the last search, adjusted by the item's search weight and frecency for
+    /// ranking
+    fn weighted_score(&self, opts: &MenuOptions) -> f32 {
+        self.score * self.menu_item.search_weight.0 + self.frecency * opts.frecency_weight
+    }
+}
+
+/// A search that is being run through the rows a chunk at a time
+struct Search {
+    query: Query,
+    // The indexes of the rows that could match, and how many of them have been checked so far
+    candidates: Vec<usize>,
+    checked: usize,
+    // The rows that passed the query's filters
+    passed: Vec<usize>,
+    last_draw: Instant,
+    // Whether the cursor is moved once the results are in. Searches that only look at newly
+    // streamed items leave it where it is
+    move_cursor: bool,
 }
 
 /// Keeps track of the state of the menu
@@ -19,92 +87,699 @@ struct MenuState {
     inputed: String,
     cursor_row: usize,
 
+    // Past searches, oldest first, which one is being shown, and what was typed before recalling
+    query_history: Vec<String>,
+    query_history_position: Option<usize>,
+    typed_query: String,
+
     // Live updated info on data rows
     rows: Vec<MenuItemKeepTrack>,
+    // The indexes of `rows` in the order they are shown, at rest and for the current search
+    rest_order: Vec<usize>,
+    order: Vec<usize>,
+
+    // The search that is still running, if there is one, and the ones that finished before it
+    // along with the rows that passed their filters. The last of those is the one being shown
+    search: Option<Search>,
+    past_searches: PastSearches,
+
+    // How frequently and recently each item was selected, by id, scaled from 0 to 1
+    frecencies: HashMap<String, f32>,
+
+    // Where new items come from while the menu is open, until the stream ends
+    stream: Option<ItemStream>,
+
+    // Finds the items for the user's search, in place of searching the rows. It is next asked
+    // for items at `source_due`, and `source_request` gets the items from the last time it was
+    source: Option<Source>,
+    source_due: Option<Instant>,
+    source_request: Option<Receiver<Vec<MenuItem>>>,
+
+    // The id of the item to put the cursor on once the search from a session is done
+    cursor_item: Option<String>,
+
+    // When the menu times out, and the number of seconds left when it was last drawn
+    deadline: Option<Instant>,
+    countdown_drawn: Option<u64>,
 
     // stored data that is only read
     term: Term,
 
     // data about the displayed menu
     lines_written: usize,
+    // How many items are on screen, and how many of them fit on one line
+    items_drawn: usize,
+    grid_columns: usize,
+
+    // The table layout, if the menu has columns, and how wide each column is drawn
+    table_columns: Vec<Column>,
+    column_widths: Vec<usize>,
+
+    // The preview pane's text for the row at `preview_row`, and how far it has been scrolled
+    preview_row: Option<usize>,
+    preview_text: Vec<String>,
+    preview_scroll: usize,
+    preview_height: usize,
 }
 
 impl MenuState {
-    /// goes through the [`MenuState`], comparing each [`MenuItem`](crate::MenuItem) comparing the
-    /// visible_name and alternative_matches to the user's input
+    /// Start a search for the user's input. The rows are compared to it by
+    /// [`MenuState::run_search`], so that a long list doesn't hold up typing
     fn search_from_inputed(&mut self, opts: &MenuOptions) {
-        // keep a count of how many rows for later use
-        let mut num_results = 0;
-
-        // For each row, fuzzy compare, average out the fuzzy score, and if it is greater than the
-        // [`MenuOptions'](crate::MenuOptions) configured min_search_threshold. If it is greater,
-        // set its visibility to true. (The visibility of the row is what decides if something is
-        // shown
-        for i in 0..self.rows.len() {
-            // The score of this row
-            let mut score = 0.0;
-
-            // If the row is already selected, and it is configured to display selected items in
-            // search results, then mark all the selected rows as visible
-            if self.rows[i].is_selected && opts.show_select_in_search {
-                self.rows[i].is_visible = true;
-                num_results += 1;
-            } else {
-                score += fuzzy_compare(
-                    &self.rows[i].menu_item.visible_name.to_lowercase(),
-                    &self.inputed.to_lowercase(),
-                );
-                if self.rows[i].menu_item.alternative_matches.is_some() {
-                    for i in self.rows[i].menu_item.alternative_matches.clone().unwrap() {
-                        score += fuzzy_compare(&i.to_lowercase(), &self.inputed.to_lowercase());
-                        score /= 2.0;
-                    }
+        // The item source does the searching instead, once the user stops typing for a moment
+        if self.source.is_some() {
+            self.source_due = Some(Instant::now() + opts.source_debounce);
+            return;
+        }
+
+        // Pull any terms with operators or fields out of the input. Only the rest is scored
+        let query = Query::parse(&self.inputed, &self.table_columns, opts);
+
+        // An empty search falls through to showing the menu at rest
+        if query.is_empty() {
+            self.search = None;
+            self.past_searches.clear();
+            self.show_rest(opts);
+            self.cursor_row = 0;
+            return;
+        }
+
+        // Only rows that are shown no matter what are visible until they have been checked
+        for row in self.rows.iter_mut() {
+            row.score = 0.0;
+            row.is_visible = row.is_selected && opts.show_select_in_search;
+        }
+
+        // If the query only adds to an earlier one's terms, the rows that didn't pass those can't
+        // pass this one's either
+        let candidates = match self.past_searches.candidates(&query) {
+            Some(passed) => passed.to_vec(),
+            None => (0..self.rows.len()).collect(),
+        };
+
+        self.search = Some(Search {
+            query,
+            candidates,
+            checked: 0,
+            passed: Vec::new(),
+            last_draw: Instant::now(),
+            move_cursor: true,
+        });
+    }
+
+    /// Keep comparing rows to the running search until it is done, or the user presses a key.
+    /// Partial results are drawn every so often if the search takes a while
+    fn run_search(
+        &mut self,
+        opts: &MenuOptions,
+        input: &InputMode,
+        preview: Option<&Preview>,
+    ) -> Result<(), std::io::Error> {
+        while let Some(search) = &mut self.search {
+            let chunk =
+                search.checked..(search.checked + SEARCH_CHUNK).min(search.candidates.len());
+            for i in search.candidates[chunk.clone()].iter().copied() {
+                let row = &mut self.rows[i];
+                if !search.query.matches(&row.keys, opts.min_search_threshold) {
+                    continue;
                 }
-                if score > opts.min_search_threshold {
-                    num_results += 1;
-                    self.rows[i].is_visible = true;
-                } else {
-                    self.rows[i].is_visible = false;
+                search.passed.push(i);
+
+                // If the row passes the query's filters and the score is greater than the
+                // [`MenuOptions'](crate::MenuOptions) configured min_search_threshold, set its
+                // visibility to true. (The visibility of the row is what decides if something is
+                // shown)
+                row.score = search.query.score(&row.keys, opts.score_aggregation);
+                if row.score > opts.min_search_threshold {
+                    row.is_visible = true;
                 }
             }
+            search.checked = chunk.end;
+
+            if search.checked == search.candidates.len() {
+                let search = self.search.take().unwrap();
+                self.past_searches.push(search.query, search.passed);
+                self.show_results(opts, search.move_cursor);
+                break;
+            }
+
+            // The key will start a new search, or it will be handled and this one picked back
+            // up afterwards
+            if input.key_pending() {
+                self.order_results(opts);
+                break;
+            }
+
+            if search.last_draw.elapsed() >= SEARCH_REDRAW_INTERVAL {
+                search.last_draw = Instant::now();
+                self.order_results(opts);
+                self.redraw(opts, preview)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sort the visible rows into the order they are shown. Pinned items come first, then the
+    /// best matches after weighting. The sort is stable, so ties keep their resting order
+    fn order_results(&mut self, opts: &MenuOptions) {
+        let mut order: Vec<usize> = self
+            .rest_order
+            .iter()
+            .copied()
+            .filter(|i| self.rows[*i].is_visible)
+            .collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (&self.rows[*a], &self.rows[*b]);
+            b.menu_item
+                .pinned
+                .cmp(&a.menu_item.pinned)
+                .then(b.weighted_score(opts).total_cmp(&a.weighted_score(opts)))
+        });
+        self.order = order;
+    }
+
+    /// Show the menu at rest
+    fn show_rest(&mut self, opts: &MenuOptions) {
+        for row in self.rows.iter_mut() {
+            row.is_visible =
+                row.menu_item.visible_at_rest || (row.is_selected && opts.show_select_in_search);
         }
+        self.order = self.rest_order.clone();
+    }
+
+    /// Show the results of the finished search, and move the cursor to match if `move_cursor`
+    /// is set
+    fn show_results(&mut self, opts: &MenuOptions, move_cursor: bool) {
+        self.order_results(opts);
+        // keep a count of how many rows for later use
+        let num_results = self.order.len();
 
         // If there are no search results, default to showing the original menu
         if num_results == 0 {
-            for i in 0..self.rows.len() {
-                if self.rows[i].menu_item.visible_at_rest {
-                    self.rows[i].is_visible = true;
-                }
-                // Assume that the there were no items shown at some point, and the cursor has been
-                // "smooshed to the ceiling"
+            self.show_rest(opts);
+            if move_cursor {
+                // Assume that the there were no items shown at some point, and the cursor has
+                // been "smooshed to the ceiling"
                 self.cursor_row = 0;
             }
-        } else {
+        } else if move_cursor {
             // Have the cursor stay in the same percentage zone of the menu (25% down before the
             // search, keep it 25% from the top, after the search)
-            if (self.lines_written as i32 - 3) <= 0 {
+            if self.items_drawn <= 1 {
                 self.cursor_row = 0;
             } else {
-                self.cursor_row = (self.cursor_row / (self.lines_written - 3)) * num_results;
+                self.cursor_row = (self.cursor_row / (self.items_drawn - 1)) * num_results;
+            }
+        }
+    }
+
+    /// Add new rows for `items`. If there is a search, the new rows are searched too
+    fn add_items(&mut self, items: Vec<MenuItem>, opts: &MenuOptions) {
+        let searching = self.search.is_some() || !self.past_searches.is_empty();
+        let first_new = self.rows.len();
+
+        for item in items {
+            let mut row = self.new_row(item, opts);
+            // During a search, new rows are hidden until they have been checked
+            row.is_visible &= !searching;
+            self.rows.push(row);
+        }
+        self.sort_rest_order(opts);
+
+        let new_rows = first_new..self.rows.len();
+        // The past searches haven't seen the new rows, so only the last one can be picked back up
+        let last_search = self.past_searches.take_last();
+        if let Some(search) = &mut self.search {
+            search.candidates.extend(new_rows);
+        } else if let Some((query, passed)) = last_search {
+            // The last search's results might have been swapped for the menu at rest, so bring
+            // them back while the new rows are checked
+            for row in self.rows.iter_mut() {
+                row.is_visible = row.is_selected && opts.show_select_in_search;
+            }
+            for i in passed.iter() {
+                let row = &mut self.rows[*i];
+                row.is_visible |= row.score > opts.min_search_threshold;
+            }
+            self.search = Some(Search {
+                query,
+                candidates: new_rows.collect(),
+                checked: 0,
+                passed,
+                last_draw: Instant::now(),
+                move_cursor: false,
+            });
+        } else {
+            self.order = self.rest_order.clone();
+        }
+    }
+
+    /// Make a row for `item`, widening any columns that it doesn't fit in
+    fn new_row(&mut self, item: MenuItem, opts: &MenuOptions) -> MenuItemKeepTrack {
+        // Columns without a fixed width are as wide as their widest cell
+        for (i, (column, width)) in self
+            .table_columns
+            .iter()
+            .zip(self.column_widths.iter_mut())
+            .enumerate()
+        {
+            if column.width.is_some() {
+                continue;
+            }
+            let cell = match &item.cells {
+                Some(cells) => cells.get(i).map(|cell| console::measure_text_width(cell)),
+                None if i == 0 => Some(console::measure_text_width(&item.visible_name)),
+                None => None,
+            };
+            *width = (*width).max(cell.unwrap_or(0));
+        }
+
+        MenuItemKeepTrack {
+            index: self.rows.len(),
+            keys: SearchKeys::new(&item, opts.normalize_unicode),
+            is_visible: item.visible_at_rest,
+            is_selected: false,
+            score: 0.0,
+            frecency: self.frecencies.get(item.get_id()).copied().unwrap_or(0.0),
+            menu_item: item,
+        }
+    }
+
+    /// Ask the item source for items if it is due, and show the items from the last time it was
+    /// asked if they have come back. Returns `true` if the items were replaced
+    fn poll_source(&mut self, opts: &MenuOptions) -> bool {
+        let Some(source) = &self.source else {
+            return false;
+        };
+
+        if self.source_due.is_some_and(|due| Instant::now() >= due) {
+            self.source_due = None;
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let source = source.clone();
+            let query = self.inputed.clone();
+            std::thread::spawn(move || {
+                // Nobody is listening if the search has changed since, which is fine
+                let _ = sender.send(source.items(&query));
+            });
+            // Dropping the last request's receiver throws away its results
+            self.source_request = Some(receiver);
+        }
+
+        let Some(request) = &self.source_request else {
+            return false;
+        };
+        match request.try_recv() {
+            Ok(items) => {
+                self.source_request = None;
+                self.replace_items(items, opts);
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+            // The callback panicked, so keep showing what was there before
+            Err(TryRecvError::Disconnected) => {
+                self.source_request = None;
+                true
+            }
+        }
+    }
+
+    /// Show `items` in place of the current rows, in the order they were given. Selected rows
+    /// are kept, so they can still be returned, and are matched to new items by their id
+    fn replace_items(&mut self, items: Vec<MenuItem>, opts: &MenuOptions) {
+        self.rows.retain(|row| row.is_selected);
+        let selected = self.rows.len();
+
+        // Columns are fit to the new items from scratch
+        self.column_widths = self
+            .table_columns
+            .iter()
+            .map(|column| {
+                column
+                    .width
+                    .unwrap_or_else(|| console::measure_text_width(&column.header))
+            })
+            .collect();
+
+        let mut order = Vec::new();
+        for (index, item) in items.into_iter().enumerate() {
+            let kept = self.rows[..selected]
+                .iter()
+                .position(|row| row.menu_item.get_id() == item.get_id());
+            match kept {
+                Some(i) if order.contains(&i) => {}
+                Some(i) => {
+                    self.rows[i].index = index;
+                    order.push(i);
+                }
+                None => {
+                    let mut row = self.new_row(item, opts);
+                    row.index = index;
+                    self.rows.push(row);
+                    order.push(self.rows.len() - 1);
+                }
+            }
+        }
+        // Selected items that weren't returned are still shown, if so configured
+        if opts.show_select_in_search {
+            for i in 0..selected {
+                if !order.contains(&i) {
+                    order.push(i);
+                }
+            }
+        }
+
+        for row in self.rows.iter_mut() {
+            row.is_visible = false;
+        }
+        for i in order.iter() {
+            self.rows[*i].is_visible = true;
+        }
+        self.rest_order = order.clone();
+        self.order = order;
+        self.cursor_row = 0;
+    }
+
+    /// Pick up the search and selection from where `session` was left. The cursor is put back
+    /// by [`MenuState::restore_cursor`] once the search is done
+    fn restore_session(&mut self, session: &MenuSession, opts: &MenuOptions) {
+        if !opts.only_one {
+            for row in self.rows.iter_mut() {
+                row.is_selected = session
+                    .selected
+                    .iter()
+                    .any(|id| id == row.menu_item.get_id());
+            }
+        }
+        self.cursor_item = session.cursor.clone();
+        if !session.query.is_empty() {
+            self.inputed = session.query.clone();
+            self.search_from_inputed(opts);
+        }
+    }
+
+    /// Put the cursor on the item from the session, if the search from the session is done
+    fn restore_cursor(&mut self) {
+        if self.search.is_some() {
+            return;
+        }
+        let Some(id) = self.cursor_item.take() else {
+            return;
+        };
+        let position = self
+            .visible_rows()
+            .position(|(_, row)| row.menu_item.get_id() == id);
+        if let Some(position) = position {
+            self.cursor_row = position;
+        }
+    }
+
+    /// Remember where the menu was left in `session`
+    fn save_session(&self, session: &mut MenuSession, opts: &MenuOptions) {
+        session.query = self.inputed.clone();
+        session.cursor = self
+            .cursor_item_index()
+            .map(|i| self.rows[i].menu_item.get_id().to_string());
+        session.selected = match opts.only_one {
+            true => Vec::new(),
+            false => self
+                .rows
+                .iter()
+                .filter(|row| row.is_selected)
+                .map(|row| row.menu_item.get_id().to_string())
+                .collect(),
+        };
+    }
+
+    /// The number of seconds left before the menu times out, rounded up
+    fn seconds_left(&self) -> Option<u64> {
+        self.deadline.map(|deadline| {
+            let left = deadline.saturating_duration_since(Instant::now());
+            left.as_millis().div_ceil(1000) as u64
+        })
+    }
+
+    /// Get the row that is chosen if the menu times out without a selection
+    fn timeout_default(&self, opts: &MenuOptions) -> Option<&MenuItemKeepTrack> {
+        let id = opts.timeout_default.as_ref()?;
+        self.rows.iter().find(|row| row.menu_item.get_id() == id)
+    }
+
+    /// The names of the items that are chosen if the menu times out
+    fn timeout_selection(&self, opts: &MenuOptions) -> Vec<String> {
+        let selected: Vec<String> = self
+            .rows
+            .iter()
+            .filter(|row| row.is_selected)
+            .map(|row| row.menu_item.visible_name.clone())
+            .collect();
+        if !selected.is_empty() {
+            return selected;
+        }
+        self.timeout_default(opts)
+            .map(|row| vec![row.menu_item.visible_name.clone()])
+            .unwrap_or_default()
+    }
+
+    /// Get the countdown shown after the user's input, if the menu can time out
+    fn countdown(&self, opts: &MenuOptions) -> Option<String> {
+        let seconds = self.seconds_left()?;
+        let choice = if self.rows.iter().any(|row| row.is_selected) {
+            "your selection".to_string()
+        } else if let Some(row) = self.timeout_default(opts) {
+            format!("'{}'", row.menu_item.visible_name)
+        } else {
+            return Some(format!("Closing in {}s…", seconds));
+        };
+        Some(format!("Continuing with {} in {}s…", choice, seconds))
+    }
+
+    /// Check if the menu is still waiting on streamed items or the item source
+    fn is_loading(&self) -> bool {
+        self.stream.is_some() || self.source_due.is_some() || self.source_request.is_some()
+    }
+
+    /// Add any items that have been streamed in since the last check, and return how many
+    /// there were
+    fn receive_items(&mut self, opts: &MenuOptions) -> usize {
+        let Some(stream) = &self.stream else {
+            return 0;
+        };
+        let (items, open) = stream.receive(STREAM_BATCH);
+        if !open {
+            self.stream = None;
+        }
+
+        let received = items.len();
+        if received > 0 {
+            self.add_items(items, opts);
+        }
+        received
+    }
+
+    /// Pinned items come first, then any items that have been selected before, most frecent
+    /// first, then items with an at_rest_position, in that order, followed by the rest in the
+    /// order they were given
+    fn sort_rest_order(&mut self, opts: &MenuOptions) {
+        let use_frecency = opts.frecency_weight > 0.0;
+        let mut rest_order: Vec<usize> = (0..self.rows.len()).collect();
+        rest_order.sort_by(|a, b| {
+            let (a, b) = (&self.rows[*a], &self.rows[*b]);
+            let position = |row: &MenuItemKeepTrack| match row.menu_item.at_rest_position {
+                Some(position) => (0, position),
+                None => (1, 0),
+            };
+            b.menu_item
+                .pinned
+                .cmp(&a.menu_item.pinned)
+                .then(if use_frecency {
+                    b.frecency.total_cmp(&a.frecency)
+                } else {
+                    std::cmp::Ordering::Equal
+                })
+                .then(position(a).cmp(&position(b)))
+        });
+        self.rest_order = rest_order;
+    }
+
+    /// Get the next older (`older == true`) or newer search in the query history, along with
+    /// where it is in the history. `None` is returned if there isn't one
+    fn recall_query(&mut self, older: bool) -> Option<(String, Option<usize>)> {
+        let position = match (self.query_history_position, older) {
+            (None, true) if !self.query_history.is_empty() => {
+                self.typed_query = self.inputed.clone();
+                Some(self.query_history.len() - 1)
+            }
+            (Some(position), true) => Some(position.saturating_sub(1)),
+            (Some(position), false) if position + 1 < self.query_history.len() => {
+                Some(position + 1)
+            }
+            // Past the newest search, back to what was typed
+            (Some(_), false) => None,
+            (None, _) => return None,
+        };
+
+        let query = match position {
+            Some(position) => self.query_history[position].clone(),
+            None => self.typed_query.clone(),
+        };
+        Some((query, position))
+    }
+
+    /// Make the changes a hook asked for. Returns `false` if it vetoed the event
+    fn apply_event(&mut self, event: MenuEvent, opts: &MenuOptions) -> bool {
+        if let Some(prompt) = event.prompt {
+            self.prompt = prompt;
+        }
+
+        if !event.replaced.is_empty() {
+            for item in event.replaced {
+                let Some(i) = self
+                    .rows
+                    .iter()
+                    .position(|row| row.menu_item.get_id() == item.get_id())
+                else {
+                    continue;
+                };
+                let row = self.new_row(item, opts);
+                let old = std::mem::replace(&mut self.rows[i], row);
+                self.rows[i].index = old.index;
+                self.rows[i].is_selected = old.is_selected;
+            }
+            self.sort_rest_order(opts);
+
+            // Search the changed items again, leaving the cursor where it is
+            let cursor_row = self.cursor_row;
+            self.past_searches.clear();
+            self.search_from_inputed(opts);
+            if let Some(search) = &mut self.search {
+                search.move_cursor = false;
             }
+            self.cursor_row = cursor_row;
         }
+
+        if !event.added.is_empty() {
+            self.add_items(event.added, opts);
+        }
+        !event.vetoed
+    }
+
+    /// Iterate over the visible rows, in the order they are shown
+    fn visible_rows(&self) -> impl Iterator<Item = (usize, &MenuItemKeepTrack)> {
+        self.order
+            .iter()
+            .map(|i| (*i, &self.rows[*i]))
+            .filter(|(_, row)| row.is_visible)
+    }
+
+    /// Get the index into `rows` of the row the user's cursor is on
+    fn cursor_item_index(&self) -> Option<usize> {
+        self.visible_rows().nth(self.cursor_row).map(|(i, _)| i)
     }
 
     /// Edit the current row's indicator to be visible on user input
     fn mark_selected(&mut self) {
-        // Poor man's "filter by visible" for loop
-        // counter keeps track of current "visible" row, and if that is the line that the user's
-        // cursor is on, sets it to selected, because that is the only row that the user could be
-        // trying to select
-        let mut counter = 0;
-        for i in 0..self.rows.len() {
-            if self.rows[i].is_visible {
-                if counter == self.cursor_row {
-                    self.rows[i].is_selected = !self.rows[i].is_selected;
+        if let Some(i) = self.cursor_item_index() {
+            self.rows[i].is_selected = !self.rows[i].is_selected;
+        }
+    }
+
+    /// Scroll the preview pane by `lines`, negative numbers scroll up
+    fn scroll_preview(&mut self, lines: isize) {
+        let max_scroll = self.preview_text.len().saturating_sub(self.preview_height);
+        self.preview_scroll = self
+            .preview_scroll
+            .saturating_add_signed(lines)
+            .min(max_scroll);
+    }
+
+    /// Get the lines of the preview pane for the row under the cursor, re-running the preview
+    /// callback if the cursor has moved to a different item. Each line is cut to `width` and at
+    /// most `height` lines are returned
+    fn get_preview_lines(&mut self, preview: &Preview, width: usize, height: usize) -> Vec<String> {
+        let cur_row = self.cursor_item_index();
+        if cur_row != self.preview_row {
+            self.preview_row = cur_row;
+            self.preview_scroll = 0;
+            self.preview_text = match cur_row {
+                Some(i) => preview
+                    .generate(&self.rows[i].menu_item)
+                    .lines()
+                    .map(|line| line.replace('\t', "    ").replace('\r', ""))
+                    .collect(),
+                None => Vec::new(),
+            };
+        }
+
+        self.preview_height = height;
+        // Keep the scroll in bounds in case the pane has grown since the last draw
+        self.scroll_preview(0);
+
+        self.preview_text
+            .iter()
+            .skip(self.preview_scroll)
+            .take(height)
+            .map(|line| console::truncate_str(line, width, "…").to_string())
+            .collect()
+    }
+
+    /// Add the preview pane to the rendered list of items, either below it or beside it
+    fn add_preview(
+        &mut self,
+        menu_string: String,
+        preview: &Preview,
+        opts: &MenuOptions,
+    ) -> String {
+        let (term_rows, term_cols) = self.term.size();
+        let (term_rows, term_cols) = (term_rows as usize, term_cols as usize);
+        // The prompt and user input take up the rest of the screen
+        let prompt_lines = self.prompt.matches('\n').count() + 1;
+        let list_lines: Vec<&str> = menu_string.lines().collect();
+
+        if opts.preview_position == PreviewPosition::Right {
+            let list_width = list_lines
+                .iter()
+                .map(|line| console::measure_text_width(line))
+                .max()
+                .unwrap_or(0);
+            // Leave room for the " │ " divider, and an empty column so lines don't wrap
+            let pane_width = term_cols.saturating_sub(list_width + 4);
+
+            if pane_width >= 10 {
+                let height = opts
+                    .preview_lines
+                    .min(term_rows.saturating_sub(prompt_lines));
+                let pane = self.get_preview_lines(preview, pane_width, height);
+
+                let mut output = String::new();
+                for i in 0..list_lines.len().max(height) {
+                    let list_part = list_lines.get(i).copied().unwrap_or("");
+                    let pane_part = pane.get(i).map(|x| x.as_str()).unwrap_or("");
+                    output +=
+                        console::pad_str(list_part, list_width, console::Alignment::Left, None)
+                            .as_ref();
+                    output += " │ ";
+                    output += pane_part;
+                    output += "\n";
                 }
-                counter += 1;
+                return output;
             }
         }
+
+        // Draw the pane below the list, with a divider between them
+        let height = opts
+            .preview_lines
+            .min(term_rows.saturating_sub(list_lines.len() + prompt_lines + 1));
+        let pane_width = term_cols.saturating_sub(1);
+        let pane = self.get_preview_lines(preview, pane_width, height);
+
+        let mut output = menu_string;
+        output += "─".repeat(pane_width).as_str();
+        output += "\n";
+        for line in pane {
+            output += line.as_str();
+            output += "\n";
+        }
+        output
     }
 
     /// Get the visible string for visible row at item index `item_index`
@@ -128,73 +803,187 @@ impl MenuState {
             false => "  ".repeat(opts.selected_indicator_width),
         };
 
-        return cursor + sel_indicator.as_str() + item.menu_item.visible_name.as_str();
+        if self.table_columns.is_empty() {
+            let mut row = cursor + sel_indicator.as_str() + item.menu_item.visible_name.as_str();
+            if let Some(description) = &item.menu_item.description {
+                row += "  ";
+                row += console::style(description).dim().to_string().as_str();
+            }
+            row
+        } else {
+            let cells: Vec<&str> = match &item.menu_item.cells {
+                Some(cells) => cells.iter().map(|cell| cell.as_str()).collect(),
+                None => vec![item.menu_item.visible_name.as_str()],
+            };
+            cursor + sel_indicator.as_str() + self.format_cells(&cells).as_str()
+        }
+    }
+
+    /// Lay out a row of the table, fitting each cell to its column
+    fn format_cells(&self, cells: &[&str]) -> String {
+        let row: Vec<String> = self
+            .table_columns
+            .iter()
+            .zip(&self.column_widths)
+            .enumerate()
+            .map(|(i, (column, width))| {
+                let mut text = Cow::Borrowed(cells.get(i).copied().unwrap_or(""));
+                // Only cut cells that don't fit, `pad_str` would also cut cells that fit exactly
+                if console::measure_text_width(&text) > *width {
+                    text = console::truncate_str(&text, width.saturating_sub(1), "")
+                        .into_owned()
+                        .into();
+                    text.to_mut().push('…');
+                }
+                console::pad_str(&text, *width, column.alignment.into(), None).to_string()
+            })
+            .collect();
+        row.join(COLUMN_GAP).trim_end().to_string()
     }
 
     fn get_menu_string(&mut self, opts: &MenuOptions) -> Result<String, std::io::Error> {
-        // Keep the number of lines the next draw will write
-        let mut next_screen_num_lines = 0;
+        let visible = self.visible_rows().map(|(_, item)| item);
 
-        // Make a multiline string that represents the next screen
-        let next_screen = {
-            let mut output = String::new();
+        let (next_screen, items_drawn, columns) = match opts.layout {
+            MenuLayout::List => {
+                let mut output = String::new();
+                let mut next_screen_num_lines = 0;
+                let mut items_drawn = 0;
 
-            // for every item we are keeping track of,
-            // if it is "visible", get_row the visible string for it and add it to the next draw
-            for i in 0..self.rows.len() {
-                let item = self.rows.get(i).unwrap();
+                // Section headings are only drawn over the items at rest
+                let show_sections = self.search.is_none() && self.past_searches.is_empty();
+                let mut section = None;
 
-                // If adding another line would make it taller than the configured max screen,
-                // break early
-                if next_screen_num_lines + 1 > opts.max_lines_visible {
-                    break;
+                // for every item that is "visible", get_row the visible string for it and add it
+                // to the next draw, stopping once the configured max screen height is reached
+                for item in visible {
+                    if next_screen_num_lines >= opts.max_lines_visible {
+                        break;
+                    }
+                    let item_section = item.menu_item.section.as_deref();
+                    if show_sections && item_section.is_some() && item_section != section {
+                        // Leave room for an item under the heading
+                        if next_screen_num_lines + 2 > opts.max_lines_visible {
+                            break;
+                        }
+                        section = item_section;
+                        output += console::style(item_section.unwrap_or_default())
+                            .bold()
+                            .to_string()
+                            .as_str();
+                        output += "\n";
+                        next_screen_num_lines += 1;
+                    }
+                    output += (self.get_row(item, items_drawn, opts) + "\n").as_str();
+                    next_screen_num_lines += 1;
+                    items_drawn += 1;
                 }
+                (output, items_drawn, 1)
+            }
+            MenuLayout::Grid => {
+                let term_width = (self.term.size().1 as usize).saturating_sub(1);
 
-                if item.is_visible {
-                    let x = self.get_row(item, next_screen_num_lines, opts) + "\n";
-                    output += x.as_str();
+                // Every cell is at least one character plus the gap, so there can't be more
+                // than this many cells on the screen
+                let max_cells = opts.max_lines_visible * (term_width / (GRID_GAP + 1)).max(1);
+                let cells: Vec<String> = visible
+                    .take(max_cells)
+                    .enumerate()
+                    .map(|(i, item)| self.get_row(item, i, opts))
+                    .collect();
 
-                    next_screen_num_lines += 1;
-                }
+                pack_grid(&cells, term_width, opts.max_lines_visible)
             }
-            output
         };
+
+        self.items_drawn = items_drawn;
+        self.grid_columns = columns;
+
+        // The header row stays above the items, no matter what is being shown
+        if !self.table_columns.is_empty() && opts.show_column_headers {
+            let headers: Vec<&str> = self
+                .table_columns
+                .iter()
+                .map(|column| column.header.as_str())
+                .collect();
+            let row_start =
+                " ".repeat(opts.cursor_width) + "  ".repeat(opts.selected_indicator_width).as_str();
+            return Ok(row_start
+                + self.format_cells(&headers).as_str()
+                + "\n"
+                + next_screen.as_str());
+        }
         Ok(next_screen)
     }
 
+    /// Clear the last menu draw, if there was one
+    fn clear(&mut self) -> Result<(), std::io::Error> {
+        if self.lines_written != 0 {
+            // This line fixes some strange bugs that included prompt lines not being deleted
+            // it does cause some flickering in generated video files however
+            self.term.clear_line()?;
+            self.term.clear_last_lines(self.lines_written - 1)?;
+            self.lines_written = 0;
+        }
+        Ok(())
+    }
+
     /// Redraw the menu based on the info in MenuState
-    fn redraw(&mut self, opts: &MenuOptions) -> Result<(), std::io::Error> {
+    fn redraw(
+        &mut self,
+        opts: &MenuOptions,
+        preview: Option<&Preview>,
+    ) -> Result<(), std::io::Error> {
         let mut next_screen: String;
-        let mut next_screen_num_lines: usize;
+        let next_screen_num_lines: usize;
+        let mut countdown_width = 0;
 
         loop {
             next_screen = self.get_menu_string(opts)?;
 
-            next_screen_num_lines = next_screen.matches('\n').count();
-            if (next_screen_num_lines as i32 - 1) < self.cursor_row as i32 {
-                self.cursor_row -= 1;
+            // Pull the cursor back onto the last item if there are fewer items than before
+            if self.items_drawn > 0 && self.cursor_row >= self.items_drawn {
+                self.cursor_row = self.items_drawn - 1;
                 continue;
             } else {
+                if let Some(preview) = preview {
+                    next_screen = self.add_preview(next_screen, preview, opts);
+                }
+                if let Some(search) = &self.search {
+                    let percent = search.checked * 100 / search.candidates.len().max(1);
+                    next_screen += format!("searching… {}%\n", percent).as_str();
+                }
+                if self.stream.is_some() {
+                    next_screen +=
+                        format!("loading… {} items\n", with_separators(self.rows.len())).as_str();
+                }
+                if self.source_due.is_some() || self.source_request.is_some() {
+                    next_screen += "searching…\n";
+                }
                 // Add the prompt and the user's input to the redraw String
                 next_screen += self.prompt.as_str();
                 next_screen += self.inputed.as_str();
+                // The countdown goes after the input, with the cursor left at the end of the
+                // input
+                if let Some(countdown) = self.countdown(opts) {
+                    let countdown = format!("  {}", countdown);
+                    countdown_width = console::measure_text_width(&countdown);
+                    next_screen += countdown.as_str();
+                }
                 next_screen_num_lines = next_screen.matches('\n').count() + 1;
                 break;
             }
         }
 
-        // Clear last menu draw, but ignore this section if it is the first draw
-        if self.lines_written != 0 {
-            // This line fixes some strange bugs that included prompt lines not being deleted
-            // it does cause some flickering in generated video files however
-            self.term.clear_line()?;
-            self.term.clear_last_lines(self.lines_written - 1)?;
-            self.lines_written = 0;
-        }
+        self.clear()?;
         // Draw the next menu
         self.term.write_all(next_screen.as_bytes())?;
+        if countdown_width > 0 {
+            self.term.move_cursor_left(countdown_width)?;
+        }
         self.term.flush()?;
         self.lines_written = next_screen_num_lines;
+        self.countdown_drawn = self.seconds_left();
 
         Ok(())
     }
@@ -203,85 +992,249 @@ impl MenuState {
 impl Menu {
     /// Serve a menu. This function is locking and requires a terminal.
     /// It returns a Vec of Strings from the items the user selected
-    pub fn serve(&self) -> Result<Option<Vec<String>>, std::io::Error> {
-        let term = Term::stdout();
+    ///
+    /// The menu is drawn on standard output, or on the controlling terminal if standard output
+    /// isn't a terminal (like when it is piped into another program), so the selection can be
+    /// printed for the next program to read.
+    ///
+    /// Pressing `Esc` closes the menu with [`Error::Cancelled`], and the menu is checked with
+    /// [`Menu::build`] before it is shown
+    pub fn serve(&self) -> Result<Option<Vec<String>>, Error> {
+        Ok(self.serve_selection()?.map(labels))
+    }
+
+    /// Serve a menu like [`Menu::serve`], returning the selected items along with where they
+    /// are in the menu. The selection can be written out with an
+    /// [`OutputFormat`](crate::OutputFormat)
+    pub fn serve_selection(&self) -> Result<Option<Vec<Selected>>, Error> {
+        self.serve_until(None, None)
+    }
+
+    /// Serve a menu like [`Menu::serve`], starting from where `session` was left, and
+    /// remembering where this one is left in it
+    pub fn serve_with(&self, session: &mut MenuSession) -> Result<Option<Vec<String>>, Error> {
+        Ok(self.serve_selection_with(session)?.map(labels))
+    }
+
+    /// Serve a menu like [`Menu::serve_selection`], starting from where `session` was left,
+    /// and remembering where this one is left in it
+    pub fn serve_selection_with(
+        &self,
+        session: &mut MenuSession,
+    ) -> Result<Option<Vec<Selected>>, Error> {
+        self.serve_until(None, Some(session))
+    }
+
+    /// Serve a menu, closing it with [`Error::Cancelled`] once `cancel` is set
+    pub(crate) fn serve_until(
+        &self,
+        cancel: Option<&AtomicBool>,
+        session: Option<&mut MenuSession>,
+    ) -> Result<Option<Vec<Selected>>, Error> {
+        self.validate()?;
 
+        let term = menu_terminal()?;
+        let input = InputMode::new()?;
+        self.serve_on(term, &input, cancel, session)
+    }
+
+    /// Serve a menu on `term`, which the menus it opens as [`submenu`](Menu::submenu)s share.
+    /// The session is updated however the menu is closed
+    fn serve_on(
+        &self,
+        term: Term,
+        input: &InputMode,
+        cancel: Option<&AtomicBool>,
+        session: Option<&mut MenuSession>,
+    ) -> Result<Option<Vec<Selected>>, Error> {
+        let history = self.history_store();
+        let mut state = self.open_state(term, history.as_deref());
+        if let Some(session) = &session {
+            state.restore_session(session, &self.configuration);
+        }
+        let selection = self.interact(&mut state, input, cancel, history);
+        if let Some(session) = session {
+            state.save_session(session, &self.configuration);
+        }
+        selection
+    }
+
+    /// Set up the state of the menu as it opens on `term`, with its items loaded and ranked by
+    /// `history`
+    fn open_state(&self, term: Term, history: Option<&dyn HistoryStore>) -> MenuState {
         let mut state = MenuState {
             prompt: self.prompt.clone(),
             lines_written: 0,
-            cursor_row: 1,
+            items_drawn: 0,
+            grid_columns: 1,
+            table_columns: self.columns.clone().unwrap_or_default(),
+            column_widths: Vec::new(),
+            cursor_row: 0,
             inputed: String::new(),
+            query_history: Vec::new(),
+            query_history_position: None,
+            typed_query: String::new(),
             rows: Vec::<MenuItemKeepTrack>::new(),
+            rest_order: Vec::new(),
+            order: Vec::new(),
+            search: None,
+            past_searches: PastSearches::default(),
+            frecencies: HashMap::new(),
+            stream: self.stream.clone(),
+            source: self.source.clone(),
+            // Ask the item source for the items to start with right away
+            source_due: self.source.as_ref().map(|_| Instant::now()),
+            source_request: None,
+            cursor_item: None,
+            deadline: self
+                .configuration
+                .timeout
+                .map(|timeout| Instant::now() + timeout),
+            countdown_drawn: None,
             term,
+            preview_row: None,
+            preview_text: Vec::new(),
+            preview_scroll: 0,
+            preview_height: 0,
         };
 
-        // Load the MenuItems into the MenuState
-        for i in 0..self.items.len() {
-            let item = self.items[i].clone();
+        if let Some(path) = &self.configuration.query_history_file {
+            // Recalling searches is a convenience, so the menu opens without them if need be
+            state.query_history = super::history::load_queries(path).unwrap_or_default();
+        }
+
+        if let Some(history) = history {
+            let now = super::history::now();
+            // The history only helps the ranking, so a menu without it is better than no menu
+            state.frecencies = history
+                .load()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|entry| {
+                    let frecency = entry.frecency(now);
+                    (entry.id, frecency)
+                })
+                .collect();
 
-            let mut is_visible = false;
-            if self.items[i].visible_at_rest {
-                is_visible = true;
+            // Scale them so that the most frecent item is 1.0
+            let max = state.frecencies.values().copied().fold(0.0, f32::max);
+            if max > 0.0 {
+                for frecency in state.frecencies.values_mut() {
+                    *frecency /= max;
+                }
             }
+        }
 
-            state.rows.push(MenuItemKeepTrack {
-                menu_item: item.clone(),
-                is_visible,
-                is_selected: false,
-            });
+        // Columns without a fixed width grow to fit their widest cell as items are added
+        state.column_widths = state
+            .table_columns
+            .iter()
+            .map(|column| {
+                column
+                    .width
+                    .unwrap_or_else(|| console::measure_text_width(&column.header))
+            })
+            .collect();
+
+        // Load the MenuItems into the MenuState, along with any that have already been streamed
+        let mut items = self.items.clone();
+        if let Some(stream) = &self.stream {
+            items.extend(stream.received());
         }
+        state.add_items(items, &self.configuration);
+        state
+    }
 
+    /// Take the user's key presses until the menu is closed, and return the selection
+    fn interact(
+        &self,
+        state: &mut MenuState,
+        input: &InputMode,
+        cancel: Option<&AtomicBool>,
+        history: Option<Arc<dyn HistoryStore>>,
+    ) -> Result<Option<Vec<Selected>>, Error> {
         loop {
-            state.redraw(&self.configuration)?;
-            let usr_key = state.term.read_key()?;
-
-            match usr_key {
-                Key::Char(c) => {
-                    if Key::Char(c) == self.configuration.select_key {
-                        state.mark_selected();
-                    } else {
-                        state.inputed.push(c);
-                        state.search_from_inputed(&self.configuration);
-                    }
-                }
-                Key::Backspace => {
-                    state.inputed.pop();
-                    state.search_from_inputed(&self.configuration);
-                }
-                Key::ArrowUp | Key::ArrowLeft => {
-                    if state.cursor_row != 0 {
-                        state.cursor_row -= 1;
-                    }
+            state.run_search(&self.configuration, input, self.preview.as_ref())?;
+            state.restore_cursor();
+            state.redraw(&self.configuration, self.preview.as_ref())?;
+
+            // Keep adding streamed items and asking the item source for items until the user
+            // presses a key. A menu that can be cancelled keeps checking for that as well
+            while state.is_loading() || cancel.is_some() || state.deadline.is_some() {
+                if cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                    state.clear()?;
+                    return Err(Error::Cancelled);
                 }
-                Key::Tab => {
-                    if (state.cursor_row as i32) <= (state.lines_written as i32) - 3 {
-                        state.cursor_row += 1;
-                    } else {
-                        state.cursor_row = 0;
+                if state.seconds_left() == Some(0) {
+                    if self.configuration.clear_menu_on_exit {
+                        state.clear()?;
                     }
+                    self.remember(state, history.as_deref());
+                    return Err(Error::Timeout(state.timeout_selection(&self.configuration)));
                 }
-                Key::ArrowDown | Key::ArrowRight => {
-                    if (state.cursor_row as i32) <= (state.lines_written as i32) - 3 {
-                        state.cursor_row += 1;
-                    }
+
+                let was_loading = state.is_loading();
+                let received = state.receive_items(&self.configuration);
+                let replaced = state.poll_source(&self.configuration);
+                let ticked = state.seconds_left() != state.countdown_drawn;
+                let loaded = was_loading && !state.is_loading();
+                if received > 0 || replaced || ticked || loaded {
+                    state.run_search(&self.configuration, input, self.preview.as_ref())?;
+                    state.redraw(&self.configuration, self.preview.as_ref())?;
                 }
-                Key::Enter => {
+                // Don't wait if the stream is sending items faster than they're being added
+                let timeout = match (received, state.source_due) {
+                    (STREAM_BATCH, _) => Duration::ZERO,
+                    (_, Some(due)) => due.saturating_duration_since(Instant::now()),
+                    (_, None) => POLL_INTERVAL,
+                };
+                if input.wait_for_key(timeout.min(POLL_INTERVAL)) {
                     break;
                 }
-                _ => {
-                    // Ignore any other keypresses
-                    //println!("Heya fella, that key hasn't been implemented yet");
-                    //std::thread::sleep(Duration::from_millis(2000));
-                    //state.lines_written += 1;
-                    continue;
+            }
+
+            let usr_key = input.read_key(&state.term)?;
+            // The cursor stays where the user puts it, even if a restored search is still running
+            state.cursor_item = None;
+            // Any key press starts the countdown over
+            if let Some(timeout) = self.configuration.timeout {
+                state.deadline = Some(Instant::now() + timeout);
+            }
+
+            // Picking an item with a submenu opens the submenu instead
+            let picked = usr_key == Key::Enter
+                || (usr_key == self.configuration.select_key && self.configuration.only_one);
+            if let Some(submenu) = picked.then(|| self.cursor_submenu(state)).flatten() {
+                state.clear()?;
+                match submenu.serve_on(state.term.clone(), input, cancel, None) {
+                    // Back to this menu, unless it is being cancelled too
+                    Err(Error::Cancelled)
+                        if !cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) =>
+                    {
+                        if let Some(timeout) = self.configuration.timeout {
+                            state.deadline = Some(Instant::now() + timeout);
+                        }
+                        continue;
+                    }
+                    selection => return selection,
                 }
             }
+
+            if self.press_key(state, usr_key)? {
+                break;
+            }
         }
 
-        let mut output: Vec<String> = Vec::new();
-        for i in state.rows {
+        if self.configuration.clear_menu_on_exit {
+            state.clear()?;
+        }
+
+        self.remember(state, history.as_deref());
+
+        let mut output: Vec<Selected> = Vec::new();
+        for i in state.rows.iter() {
             if i.is_selected {
-                output.push(i.menu_item.visible_name);
+                output.push(Selected::new(i.index, i.menu_item.clone()));
             }
         }
 
@@ -291,4 +1244,926 @@ impl Menu {
             Ok(Some(output))
         }
     }
+
+    /// Act on a key the user pressed. Returns `true` once the menu should close with the
+    /// selection
+    fn press_key(&self, state: &mut MenuState, usr_key: Key) -> Result<bool, Error> {
+        // The select key can be any key, so check it before the other controls
+        if usr_key == self.configuration.select_key {
+            if !self.run_hook(self.hooks.toggle.as_ref(), state, None) {
+                return Ok(false);
+            }
+            state.mark_selected();
+            if self.configuration.only_one {
+                if self.run_hook(self.hooks.submit.as_ref(), state, None) {
+                    return Ok(true);
+                }
+                // Unselect it again, so the next pick isn't mixed up with it
+                state.mark_selected();
+            }
+            return Ok(false);
+        }
+        if self.configuration.query_history_file.is_some() {
+            let (older, newer) = &self.configuration.query_history_keys;
+            if usr_key == *older || usr_key == *newer {
+                if let Some((query, position)) = state.recall_query(usr_key == *older) {
+                    self.change_query(state, query, position);
+                }
+                return Ok(false);
+            }
+        }
+
+        let cursor_row = state.cursor_row;
+        match usr_key {
+            Key::Char(c) => {
+                let mut query = state.inputed.clone();
+                query.push(c);
+                self.change_query(state, query, None);
+            }
+            Key::Backspace => {
+                let mut query = state.inputed.clone();
+                query.pop();
+                self.change_query(state, query, None);
+            }
+            // With a single column (the list layout), all four arrows move between rows
+            Key::ArrowUp => {
+                if state.cursor_row >= state.grid_columns {
+                    state.cursor_row -= state.grid_columns;
+                }
+            }
+            Key::ArrowLeft => {
+                if state.cursor_row != 0 {
+                    state.cursor_row -= 1;
+                }
+            }
+            Key::Tab => {
+                if state.cursor_row + 1 < state.items_drawn {
+                    state.cursor_row += 1;
+                } else {
+                    state.cursor_row = 0;
+                }
+            }
+            Key::ArrowDown => {
+                if state.cursor_row + state.grid_columns < state.items_drawn {
+                    state.cursor_row += state.grid_columns;
+                }
+            }
+            Key::ArrowRight => {
+                if state.cursor_row + 1 < state.items_drawn {
+                    state.cursor_row += 1;
+                }
+            }
+            Key::PageUp => {
+                state.scroll_preview(-(state.preview_height as isize));
+            }
+            Key::PageDown => {
+                state.scroll_preview(state.preview_height as isize);
+            }
+            Key::Enter => {
+                if self.submit(state) {
+                    return Ok(true);
+                }
+            }
+            Key::Escape => {
+                if self.configuration.clear_menu_on_exit {
+                    state.clear()?;
+                }
+                return Err(Error::Cancelled);
+            }
+            _ => {
+                // Ignore any other keypresses
+                //println!("Heya fella, that key hasn't been implemented yet");
+                //std::thread::sleep(Duration::from_millis(2000));
+                //state.lines_written += 1;
+                return Ok(false);
+            }
+        }
+
+        let navigated = matches!(
+            usr_key,
+            Key::ArrowUp | Key::ArrowDown | Key::ArrowLeft | Key::ArrowRight | Key::Tab
+        );
+        if navigated
+            && state.cursor_row != cursor_row
+            && !self.run_hook(self.hooks.cursor_move.as_ref(), state, None)
+        {
+            state.cursor_row = cursor_row;
+        }
+        Ok(false)
+    }
+
+    /// Save the search in the query history and the selected items in the selection history,
+    /// as the menu closes with a selection. A menu that times out only records the items the
+    /// user selected, not its [`timeout_default`](MenuOptions::timeout_default), since that
+    /// wasn't picked
+    fn remember(&self, state: &MenuState, history: Option<&dyn HistoryStore>) {
+        if let Some(path) = &self.configuration.query_history_file {
+            if !state.inputed.trim().is_empty() {
+                // Failing to remember the search shouldn't lose the selection
+                let _ = super::history::save_query(path, &state.inputed);
+            }
+        }
+
+        if let Some(history) = history {
+            let ids: Vec<&str> = state
+                .rows
+                .iter()
+                .filter(|row| row.is_selected)
+                .map(|row| row.menu_item.get_id())
+                .collect();
+            // Failing to record the selection shouldn't lose it
+            if !ids.is_empty() {
+                let _ = history.record(&ids);
+            }
+        }
+    }
+
+    /// Close the menu with the selection, unless the submit hook vetoes it. With nothing
+    /// selected, the item under the cursor is picked. Returns `true` if the menu should close
+    fn submit(&self, state: &mut MenuState) -> bool {
+        let pick_cursor = !state.rows.iter().any(|row| row.is_selected);
+        if pick_cursor {
+            state.mark_selected();
+        }
+        if self.run_hook(self.hooks.submit.as_ref(), state, None) {
+            return true;
+        }
+        if pick_cursor {
+            state.mark_selected();
+        }
+        false
+    }
+
+    /// Change the search to `query`, unless the query change hook vetoes it. `position` is
+    /// where the query is in the query history, if it was recalled from there
+    fn change_query(&self, state: &mut MenuState, query: String, position: Option<usize>) {
+        if query != state.inputed
+            && !self.run_hook(self.hooks.query_change.as_ref(), state, Some(query.clone()))
+        {
+            return;
+        }
+        state.inputed = query;
+        state.query_history_position = position;
+        state.search_from_inputed(&self.configuration);
+    }
+
+    /// Run `hook`, if the menu has one for the event, about the item under the cursor. `query`
+    /// is the search the event changes it to. Returns `false` if the hook vetoed the event
+    fn run_hook(&self, hook: Option<&Hook>, state: &mut MenuState, query: Option<String>) -> bool {
+        let Some(hook) = hook else {
+            return true;
+        };
+        let mut event = MenuEvent {
+            item: state
+                .cursor_item_index()
+                .map(|i| state.rows[i].menu_item.clone()),
+            query: query.unwrap_or_else(|| state.inputed.clone()),
+            selected: state
+                .rows
+                .iter()
+                .filter(|row| row.is_selected)
+                .map(|row| row.menu_item.clone())
+                .collect(),
+            vetoed: false,
+            replaced: Vec::new(),
+            added: Vec::new(),
+            prompt: None,
+        };
+        hook.call(&mut event);
+        state.apply_event(event, &self.configuration)
+    }
+
+    /// Get the submenu of the item under the cursor, if it has one
+    fn cursor_submenu(&self, state: &MenuState) -> Option<&Menu> {
+        let row = &state.rows[state.cursor_item_index()?];
+        self.submenus.get(row.menu_item.get_id())
+    }
+}
+
+/// Get the terminal to draw the menu on: standard output, or the controlling terminal when
+/// standard output is piped somewhere else
+fn menu_terminal() -> Result<Term, Error> {
+    let term = Term::stdout();
+    if term.is_term() {
+        return Ok(term);
+    }
+
+    #[cfg(unix)]
+    if let Ok(tty) = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+    {
+        let term = Term::read_write_pair(tty.try_clone()?, tty);
+        if term.is_term() {
+            return Ok(term);
+        }
+    }
+    Err(Error::NotATerminal)
+}
+
+#[cfg(feature = "async")]
+impl Menu {
+    /// Serve a menu from async code. The menu runs on tokio's blocking thread pool, so waiting
+    /// on the user doesn't hold up the runtime, and otherwise works just like [`Menu::serve`].
+    ///
+    /// Dropping the future, like when another branch of a `tokio::select!` finishes first,
+    /// cancels the menu. On a multi-threaded runtime, the drop waits for the menu to be cleared
+    /// from the terminal, so that it can't clear anything printed after it, with
+    /// [`block_in_place`](tokio::task::block_in_place) so that other tasks keep running in the
+    /// meantime. A current thread runtime has nowhere else to run them, so there the drop returns
+    /// right away, and the menu clears itself a few dozen milliseconds later.
+    ///
+    /// On Unix, the menu keeps checking for cancellation while it waits for a key press. Other
+    /// platforms can only check between key presses, so there a dropped menu stays open until
+    /// the user presses a key.
+    ///
+    /// Items can be added while the menu is open with [`Menu::stream`], as sending on a
+    /// [`std::sync::mpsc::Sender`] never blocks.
+    ///
+    /// This is only available with the `async` feature, and needs to be run inside of a tokio
+    /// runtime.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use cartographer_rs::{menu, menu_item};
+    /// use std::time::Duration;
+    ///
+    /// # async fn run() -> Result<(), cartographer_rs::Error> {
+    /// let menu = menu!("Pick one within 10 seconds: ", [menu_item!("yes"), menu_item!("no")]);
+    ///
+    /// tokio::select! {
+    ///     selection = menu.serve_async() => println!("{:?}", selection?),
+    ///     _ = tokio::time::sleep(Duration::from_secs(10)) => println!("Too slow!"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn serve_async(&self) -> Result<Option<Vec<String>>, Error> {
+        /// Cancels the menu when the future is dropped, and waits for it to be cleared away so
+        /// that it can't clear anything printed after it
+        struct CancelOnDrop {
+            cancel: Arc<AtomicBool>,
+            done: Receiver<()>,
+        }
+
+        impl Drop for CancelOnDrop {
+            fn drop(&mut self) {
+                self.cancel.store(true, Ordering::Relaxed);
+                // The menu checks for this every few dozen milliseconds, so this shouldn't take
+                // long, but it would still hold up every other task on this thread
+                let wait = || {
+                    let _ = self.done.recv_timeout(Duration::from_millis(500));
+                };
+                match tokio::runtime::Handle::try_current() {
+                    Ok(runtime) => {
+                        if runtime.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread {
+                            tokio::task::block_in_place(wait);
+                        }
+                    }
+                    // Outside of a runtime, there's nothing to hold up
+                    Err(_) => wait(),
+                }
+            }
+        }
+
+        let menu = self.clone();
+        let (done, done_receiver) = std::sync::mpsc::channel::<()>();
+        let cancel = CancelOnDrop {
+            cancel: Arc::new(AtomicBool::new(false)),
+            done: done_receiver,
+        };
+        let task_cancel = cancel.cancel.clone();
+
+        let task = tokio::task::spawn_blocking(move || {
+            let selection = menu.serve_until(Some(&task_cancel), None);
+            drop(done);
+            selection
+        });
+        match task.await {
+            Ok(selection) => Ok(selection?.map(labels)),
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(Error::Io(std::io::Error::other(err))),
+        }
+    }
+}
+
+/// Get the labels of the selected items, which is what [`Menu::serve`] returns
+fn labels(selection: Vec<Selected>) -> Vec<String> {
+    selection
+        .into_iter()
+        .map(|selected| selected.label().to_string())
+        .collect()
+}
+
+/// Format a number with commas between every three digits
+fn with_separators(number: usize) -> String {
+    let digits = number.to_string();
+    let mut output = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            output.push(',');
+        }
+        output.push(digit);
+    }
+    output
+}
+
+/// Lay `cells` out in a grid `width` characters wide and at most `max_lines` lines tall.
+/// Returns the grid, the number of cells in it, and the number of cells per line
+fn pack_grid(cells: &[String], width: usize, max_lines: usize) -> (String, usize, usize) {
+    // Make every cell as wide as the widest one, and fit as many as we can per line
+    let cell_width = cells
+        .iter()
+        .map(|cell| console::measure_text_width(cell))
+        .max()
+        .unwrap_or(0);
+    let columns = ((width + GRID_GAP) / (cell_width + GRID_GAP)).max(1);
+
+    let mut output = String::new();
+    let mut items_drawn = 0;
+    for line in cells.chunks(columns).take(max_lines) {
+        let line: Vec<String> = line
+            .iter()
+            .map(|cell| {
+                console::pad_str(cell, cell_width, console::Alignment::Left, None).to_string()
+            })
+            .collect();
+        items_drawn += line.len();
+        output += line.join(" ".repeat(GRID_GAP).as_str()).trim_end();
+        output += "\n";
+    }
+    (output, items_drawn, columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::history::{load_queries, save_query};
+    use crate::{menu, menu_item, ScoreAggregation};
+
+    /// Open `menu` without drawing it, and finish the search for `query`
+    fn open(menu: &Menu, query: &str) -> MenuState {
+        let mut state = menu.open_state(Term::stdout(), None);
+        menu.change_query(&mut state, query.to_string(), None);
+        finish_search(menu, &mut state);
+        state
+    }
+
+    fn finish_search(menu: &Menu, state: &mut MenuState) {
+        state
+            .run_search(&menu.configuration, &InputMode::new().unwrap(), None)
+            .unwrap();
+    }
+
+    /// Press the keys that type `text`, and finish the search
+    fn type_text(menu: &Menu, state: &mut MenuState, text: &str) {
+        for c in text.chars() {
+            assert!(!menu.press_key(state, Key::Char(c)).unwrap());
+        }
+        finish_search(menu, state);
+    }
+
+    fn selected(state: &MenuState) -> Vec<&str> {
+        state
+            .rows
+            .iter()
+            .filter(|row| row.is_selected)
+            .map(|row| row.menu_item.name())
+            .collect()
+    }
+
+    fn shown(state: &MenuState) -> Vec<&str> {
+        state
+            .visible_rows()
+            .map(|(_, row)| row.menu_item.name())
+            .collect()
+    }
+
+    #[test]
+    fn vetoed_query_changes_keep_the_search() {
+        let menu =
+            menu!("> ", [menu_item!("apple"), menu_item!("banana")]).on_query_change(|event| {
+                if event.query().contains('x') {
+                    event.veto();
+                }
+            });
+        let mut state = open(&menu, "ban");
+        assert_eq!(state.inputed, "ban");
+
+        menu.change_query(&mut state, "banx".to_string(), None);
+        assert_eq!(state.inputed, "ban");
+        assert_eq!(shown(&state), ["banana"]);
+    }
+
+    #[test]
+    fn vetoed_toggles_are_reported() {
+        let menu = menu!("> ", [menu_item!("apple"), menu_item!("banana")]).on_toggle(|event| {
+            if event.item().is_some_and(|item| item.name() == "apple") {
+                event.veto();
+            }
+        });
+        let mut state = open(&menu, "");
+        assert!(!menu.run_hook(menu.hooks.toggle.as_ref(), &mut state, None));
+
+        state.cursor_row = 1;
+        assert!(menu.run_hook(menu.hooks.toggle.as_ref(), &mut state, None));
+        // Events without a hook always go ahead
+        assert!(menu.run_hook(menu.hooks.submit.as_ref(), &mut state, None));
+    }
+
+    #[test]
+    fn hooks_replace_and_add_items() {
+        let menu = menu!("> ", [menu_item!("apple"), menu_item!("banana")]).on_submit(|event| {
+            let item = event.item().unwrap().clone();
+            event.replace_item(item.description("picked".to_string()));
+            event.push_item(menu_item!("cherry"));
+        });
+        let mut state = open(&menu, "");
+        assert!(menu.run_hook(menu.hooks.submit.as_ref(), &mut state, None));
+        finish_search(&menu, &mut state);
+
+        assert_eq!(state.rows[0].menu_item.get_description(), Some("picked"));
+        assert_eq!(state.rows[1].menu_item.get_description(), None);
+        assert_eq!(shown(&state), ["apple", "banana", "cherry"]);
+    }
+
+    #[test]
+    fn hooks_change_the_prompt() {
+        let menu =
+            menu!("> ", [menu_item!("apple"), menu_item!("banana")]).on_cursor_move(|event| {
+                let prompt = format!("{}> ", event.item().unwrap().name());
+                event.set_prompt(prompt);
+            });
+        let mut state = open(&menu, "");
+        assert_eq!(state.prompt, "> ");
+
+        state.cursor_row = 1;
+        menu.run_hook(menu.hooks.cursor_move.as_ref(), &mut state, None);
+        assert_eq!(state.prompt, "banana> ");
+    }
+
+    #[test]
+    fn enter_picks_the_selection_or_the_cursor_item() {
+        for only_one in [false, true] {
+            let options = MenuOptions::new().only_one_selection(only_one);
+            let menu = menu!("> ", options, [menu_item!("apple"), menu_item!("banana")]);
+            let mut state = open(&menu, "");
+            state.cursor_row = 1;
+            assert!(menu.submit(&mut state));
+            assert_eq!(selected(&state), ["banana"]);
+        }
+
+        // Items that were picked with the select key are returned instead of the cursor item
+        let menu = menu!(
+            "> ",
+            [
+                menu_item!("apple"),
+                menu_item!("banana"),
+                menu_item!("cherry")
+            ]
+        );
+        let mut state = open(&menu, "");
+        state.mark_selected();
+        state.cursor_row = 2;
+        state.mark_selected();
+        state.cursor_row = 1;
+        assert!(menu.submit(&mut state));
+        assert_eq!(selected(&state), ["apple", "cherry"]);
+    }
+
+    #[test]
+    fn vetoed_submits_leave_nothing_picked() {
+        let menu = menu!("> ", [menu_item!("apple"), menu_item!("banana")]).on_submit(|event| {
+            event.veto();
+        });
+        let mut state = open(&menu, "");
+        assert!(!menu.submit(&mut state));
+        assert!(selected(&state).is_empty());
+    }
+
+    #[test]
+    fn searching_for_a_file_and_pressing_enter_picks_it() {
+        // The options `cartographer --multi` uses, as the shell's Ctrl-T binding does
+        let options = MenuOptions::new().only_one_selection(false);
+        let files = ["Cargo.toml", "src/lib.rs", "src/main.rs", "README.org"];
+        let menu = Menu::new(
+            "Files: ".to_string(),
+            files.iter().map(|file| menu_item!(file)).collect(),
+            Some(options),
+        );
+        let mut state = open(&menu, "main");
+        assert!(menu.submit(&mut state));
+        assert_eq!(selected(&state), ["src/main.rs"]);
+    }
+
+    #[test]
+    fn typed_searches_can_have_several_field_terms() {
+        let menu = menu!(
+            "> ",
+            [
+                menu_item!("api-1", cells = ["eu-west", "running"]),
+                menu_item!("api-2", cells = ["us-east", "running"]),
+                menu_item!("web", cells = ["eu-west", "running"]),
+                menu_item!("api-3", cells = ["eu-west", "stopped"]),
+            ]
+        )
+        .columns(vec![Column::new("Region"), Column::new("Status")]);
+        let mut state = open(&menu, "");
+        type_text(&menu, &mut state, "region:eu status:running api");
+
+        assert_eq!(state.inputed, "region:eu status:running api");
+        assert!(selected(&state).is_empty());
+        assert_eq!(shown(&state), ["api-1"]);
+
+        // Items are selected with Tab instead
+        assert!(!menu.press_key(&mut state, Key::Tab).unwrap());
+        assert_eq!(selected(&state), ["api-1"]);
+    }
+
+    #[test]
+    fn typed_searches_can_use_operators() {
+        let menu = menu!(
+            "> ",
+            [
+                menu_item!("foo"),
+                menu_item!("foo bar"),
+                menu_item!("foobar"),
+                menu_item!("baz"),
+            ]
+        );
+        let mut state = open(&menu, "");
+        type_text(&menu, &mut state, "foo !bar");
+        assert_eq!(shown(&state), ["foo"]);
+
+        let mut state = open(&menu, "");
+        type_text(&menu, &mut state, "bar$ | ^baz");
+        assert_eq!(shown(&state), ["foo bar", "foobar", "baz"]);
+    }
+
+    #[test]
+    fn exact_aliases_rank_above_weak_names() {
+        let items = vec![
+            menu_item!("predictor"),
+            menu_item!("xyz", aliases = ["other", "prod"]),
+        ];
+        let menu = Menu::new("> ".to_string(), items.clone(), None);
+        assert_eq!(shown(&open(&menu, "prod")), ["xyz", "predictor"]);
+
+        // Unless the aliases are weighted down, here so far that they don't count at all
+        let options = MenuOptions::new().score_aggregation(ScoreAggregation::Weighted {
+            label: 1.0,
+            alternatives: 0.0,
+        });
+        let menu = Menu::new("> ".to_string(), items, Some(options));
+        assert_eq!(shown(&open(&menu, "prod")), ["predictor"]);
+    }
+
+    #[test]
+    fn pinned_items_stay_on_top() {
+        let menu = menu!(
+            "> ",
+            [
+                menu_item!("apple"),
+                menu_item!("apple crumble", pinned),
+                menu_item!("banana"),
+            ]
+        );
+        assert_eq!(
+            shown(&open(&menu, "")),
+            ["apple crumble", "apple", "banana"]
+        );
+        // Even when other items match the search better
+        assert_eq!(shown(&open(&menu, "apple")), ["apple crumble", "apple"]);
+    }
+
+    #[test]
+    fn weights_break_ties() {
+        let items = vec![menu_item!("report a"), menu_item!("report b")];
+        let menu = Menu::new("> ".to_string(), items.clone(), None);
+        // Ties keep the order the items have at rest
+        assert_eq!(shown(&open(&menu, "report")), ["report a", "report b"]);
+
+        let items = vec![items[0].clone(), items[1].clone().search_weight(2.0)];
+        let menu = Menu::new("> ".to_string(), items, None);
+        assert_eq!(shown(&open(&menu, "report")), ["report b", "report a"]);
+        // Weights only apply to search results
+        assert_eq!(shown(&open(&menu, "")), ["report a", "report b"]);
+    }
+
+    #[test]
+    fn timeouts_choose_the_selection_or_the_default() {
+        let items = vec![
+            menu_item!("apple"),
+            menu_item!("banana", id = "b"),
+            menu_item!("cherry"),
+        ];
+        let options = MenuOptions::new().timeout(Duration::from_secs(5));
+        let menu = Menu::new("> ".to_string(), items.clone(), Some(options.clone()));
+        let state = open(&menu, "");
+        assert!(state.timeout_selection(&menu.configuration).is_empty());
+
+        let options = options.timeout_default("b");
+        let menu = Menu::new("> ".to_string(), items, Some(options));
+        let mut state = open(&menu, "");
+        assert_eq!(state.timeout_selection(&menu.configuration), ["banana"]);
+
+        state.mark_selected();
+        state.cursor_row = 2;
+        state.mark_selected();
+        assert_eq!(
+            state.timeout_selection(&menu.configuration),
+            ["apple", "cherry"]
+        );
+    }
+
+    #[test]
+    fn only_picked_items_are_remembered() {
+        #[derive(Default)]
+        struct Recorded(std::sync::Mutex<Vec<Vec<String>>>);
+
+        impl HistoryStore for Recorded {
+            fn load(&self) -> std::io::Result<Vec<crate::HistoryEntry>> {
+                Ok(Vec::new())
+            }
+
+            fn record(&self, ids: &[&str]) -> std::io::Result<()> {
+                let ids = ids.iter().map(|id| id.to_string()).collect();
+                self.0.lock().unwrap().push(ids);
+                Ok(())
+            }
+        }
+
+        let options = MenuOptions::new()
+            .timeout(Duration::from_secs(5))
+            .timeout_default("b");
+        let menu = menu!("> ", options, [menu_item!("a"), menu_item!("b")]);
+        let recorded = Recorded::default();
+        let mut state = open(&menu, "");
+
+        // Timing out into the default records nothing
+        menu.remember(&state, Some(&recorded));
+        assert!(recorded.0.lock().unwrap().is_empty());
+
+        state.mark_selected();
+        menu.remember(&state, Some(&recorded));
+        assert_eq!(*recorded.0.lock().unwrap(), [["a"]]);
+    }
+
+    #[test]
+    fn searches_are_recalled_from_the_query_history() {
+        let dir = std::env::temp_dir().join(format!("cartographer-recall-{}", std::process::id()));
+        let path = dir.join("queries");
+        save_query(&path, "apple").unwrap();
+        save_query(&path, "banana").unwrap();
+
+        let options = MenuOptions::new().query_history_file(&path);
+        let menu = menu!("> ", options, [menu_item!("apple"), menu_item!("banana")]);
+        let (older, newer) = menu.configuration.query_history_keys.clone();
+        let mut state = open(&menu, "");
+        type_text(&menu, &mut state, "ch");
+
+        let mut press = |key: &Key| {
+            assert!(!menu.press_key(&mut state, key.clone()).unwrap());
+            state.inputed.clone()
+        };
+        assert_eq!(press(&older), "banana");
+        assert_eq!(press(&older), "apple");
+        assert_eq!(press(&older), "apple");
+        assert_eq!(press(&newer), "banana");
+        // Past the newest search is what was typed
+        assert_eq!(press(&newer), "ch");
+
+        // Closing the menu saves the search
+        menu.remember(&state, None);
+        assert_eq!(load_queries(&path).unwrap(), ["apple", "banana", "ch"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn streamed_items_join_the_search() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        sender.send(menu_item!("apple")).unwrap();
+        let menu = menu!("> ", [menu_item!("banana")]).stream(receiver);
+        let mut state = open(&menu, "");
+        assert_eq!(shown(&state), ["banana"]);
+        assert_eq!(state.receive_items(&menu.configuration), 1);
+        assert_eq!(shown(&state), ["banana", "apple"]);
+
+        type_text(&menu, &mut state, "ap");
+        sender.send(menu_item!("apricot")).unwrap();
+        sender.send(menu_item!("cherry")).unwrap();
+        assert_eq!(state.receive_items(&menu.configuration), 2);
+        finish_search(&menu, &mut state);
+        assert_eq!(shown(&state), ["apple", "apricot"]);
+        assert!(state.is_loading());
+
+        // The menu stops waiting for items once the sender is dropped
+        drop(sender);
+        assert_eq!(state.receive_items(&menu.configuration), 0);
+        assert!(!state.is_loading());
+
+        // Clearing the search shows everything that arrived
+        for _ in 0..2 {
+            menu.press_key(&mut state, Key::Backspace).unwrap();
+        }
+        assert_eq!(shown(&state), ["banana", "apple", "apricot", "cherry"]);
+
+        // And serving the menu again starts with them
+        assert_eq!(
+            shown(&open(&menu, "")),
+            ["banana", "apple", "apricot", "cherry"]
+        );
+    }
+
+    /// Wait for the menu's item source to answer, and search its items
+    fn wait_for_source(menu: &Menu, state: &mut MenuState) {
+        let start = Instant::now();
+        while !state.poll_source(&menu.configuration) {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "the source didn't answer"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        finish_search(menu, state);
+    }
+
+    #[test]
+    fn item_sources_replace_the_items_as_the_search_changes() {
+        let words = ["apple", "banana", "blueberry", "cherry"];
+        let options = MenuOptions::new().source_debounce(Duration::ZERO);
+        let menu = menu!("> ", options, [menu_item!("loading")]).source(move |query| {
+            words
+                .iter()
+                .filter(|word| word.starts_with(query))
+                .map(|word| menu_item!(word))
+                .collect()
+        });
+
+        // The menu's own items are shown until the source answers the empty search
+        let mut state = open(&menu, "");
+        assert_eq!(shown(&state), ["loading"]);
+        wait_for_source(&menu, &mut state);
+        assert_eq!(shown(&state), words);
+
+        state.cursor_row = 3;
+        menu.press_key(&mut state, Key::Tab).unwrap();
+        type_text(&menu, &mut state, "b");
+        assert!(state.is_loading());
+        wait_for_source(&menu, &mut state);
+        assert!(!state.is_loading());
+        // The selected item is kept, to be returned, even though the source didn't return it
+        assert_eq!(shown(&state), ["banana", "blueberry", "cherry"]);
+        assert_eq!(selected(&state), ["cherry"]);
+
+        type_text(&menu, &mut state, "l");
+        wait_for_source(&menu, &mut state);
+        assert_eq!(shown(&state), ["blueberry", "cherry"]);
+        assert_eq!(selected(&state), ["cherry"]);
+    }
+
+    #[test]
+    fn sessions_bring_back_the_search_cursor_and_selection() {
+        let menu = menu!(
+            "> ",
+            [
+                menu_item!("apple"),
+                menu_item!("banana"),
+                menu_item!("bandana"),
+                menu_item!("cherry"),
+            ]
+        );
+        let mut state = open(&menu, "");
+        menu.press_key(&mut state, Key::Tab).unwrap();
+        type_text(&menu, &mut state, "ban");
+        state.cursor_row = shown(&state)
+            .iter()
+            .position(|name| *name == "bandana")
+            .unwrap();
+
+        let mut session = MenuSession::new();
+        state.save_session(&mut session, &menu.configuration);
+
+        let mut state = menu.open_state(Term::stdout(), None);
+        state.restore_session(&session, &menu.configuration);
+        finish_search(&menu, &mut state);
+        state.restore_cursor();
+        assert_eq!(state.inputed, "ban");
+        // The selected item is shown as well, ranked last
+        assert_eq!(shown(&state), ["banana", "bandana", "apple"]);
+        assert_eq!(shown(&state)[state.cursor_row], "bandana");
+        assert_eq!(selected(&state), ["apple"]);
+
+        // A menu that only picks one item has nothing selected to bring back
+        let mut menu = menu;
+        menu.set_options(MenuOptions::new().only_one_selection(true));
+        let mut state = menu.open_state(Term::stdout(), None);
+        state.restore_session(&session, &menu.configuration);
+        assert!(selected(&state).is_empty());
+    }
+
+    #[test]
+    fn cells_are_fit_to_their_columns() {
+        let menu = menu!(
+            "> ",
+            [
+                menu_item!("a", cells = ["eu-west", "prod", "7"]),
+                menu_item!("b", cells = ["us", "staging", "12"]),
+            ]
+        )
+        .columns(vec![
+            Column::new("Region"),
+            Column::new("Env").width(4),
+            Column::new("Count").alignment(crate::ColumnAlignment::Right),
+        ]);
+        let state = open(&menu, "");
+
+        // Columns without a width are as wide as their header or widest cell
+        assert_eq!(state.column_widths, [7, 4, 5]);
+        assert_eq!(
+            state.format_cells(&["eu-west", "prod", "7"]),
+            "eu-west  prod      7"
+        );
+        // Only cells wider than their column are cut
+        assert_eq!(
+            state.format_cells(&["us", "staging", "12"]),
+            "us       sta…     12"
+        );
+        assert_eq!(
+            state.format_cells(&["us", "日本語", "12"]),
+            "us       日…      12"
+        );
+        // Missing cells are blank, and nothing trails the last cell
+        assert_eq!(state.format_cells(&["us"]), "us");
+        assert_eq!(state.format_cells(&["", "prod"]), "         prod");
+    }
+
+    #[test]
+    fn grids_fit_as_many_cells_per_line_as_they_can() {
+        let cells: Vec<String> = ["a", "bb", "ccc", "dddd", "e", "f", "g"]
+            .iter()
+            .map(|cell| cell.to_string())
+            .collect();
+
+        // Three cells as wide as the widest one and two gaps between them fit exactly
+        assert_eq!(
+            pack_grid(&cells, 16, 5),
+            ("a     bb    ccc\ndddd  e     f\ng\n".to_string(), 7, 3)
+        );
+        assert_eq!(pack_grid(&cells, 15, 5).2, 2);
+        // Lines past the last visible one aren't drawn
+        assert_eq!(pack_grid(&cells, 16, 2).1, 6);
+        // Cells wider than the screen still get a line each
+        assert_eq!(pack_grid(&cells, 2, 10).2, 1);
+    }
+
+    #[test]
+    fn arrow_keys_move_around_the_grid() {
+        let menu = menu!("> ", [menu_item!("a"), menu_item!("b")]);
+        let mut state = open(&menu, "");
+        state.grid_columns = 3;
+        state.items_drawn = 7;
+        state.cursor_row = 1;
+
+        let mut press = |key: Key| {
+            menu.press_key(&mut state, key).unwrap();
+            state.cursor_row
+        };
+        assert_eq!(press(Key::ArrowDown), 4);
+        // There is no item below the fifth one
+        assert_eq!(press(Key::ArrowDown), 4);
+        assert_eq!(press(Key::ArrowRight), 5);
+        assert_eq!(press(Key::ArrowRight), 6);
+        assert_eq!(press(Key::ArrowRight), 6);
+        assert_eq!(press(Key::ArrowUp), 3);
+        assert_eq!(press(Key::ArrowUp), 0);
+        assert_eq!(press(Key::ArrowUp), 0);
+        assert_eq!(press(Key::ArrowLeft), 0);
+    }
+
+    #[test]
+    fn previews_are_generated_once_per_item_and_scroll() {
+        let generated = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = generated.clone();
+        let menu = menu!("> ", [menu_item!("a"), menu_item!("b")]).preview(move |item| {
+            log.lock().unwrap().push(item.name().to_string());
+            format!("{}\n\tline 2\r\nline 3 is long", item.name())
+        });
+        let preview = menu.preview.clone().unwrap();
+        let mut state = open(&menu, "");
+
+        assert_eq!(state.get_preview_lines(&preview, 8, 2), ["a", "    lin…"]);
+        state.scroll_preview(5);
+        assert_eq!(
+            state.get_preview_lines(&preview, 8, 2),
+            ["    lin…", "line 3 …"]
+        );
+        assert_eq!(*generated.lock().unwrap(), ["a"]);
+
+        // Moving to another item starts its preview from the top
+        state.cursor_row = 1;
+        assert_eq!(state.get_preview_lines(&preview, 8, 2), ["b", "    lin…"]);
+        assert_eq!(*generated.lock().unwrap(), ["a", "b"]);
+    }
 }
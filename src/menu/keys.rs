@@ -0,0 +1,240 @@
+use crate::Error;
+
+/// The names of the keys that aren't written as the character they type
+const KEY_NAMES: [(&str, console::Key); 16] = [
+    ("space", console::Key::Char(' ')),
+    ("tab", console::Key::Tab),
+    ("shift-tab", console::Key::BackTab),
+    ("enter", console::Key::Enter),
+    ("esc", console::Key::Escape),
+    ("backspace", console::Key::Backspace),
+    ("delete", console::Key::Del),
+    ("insert", console::Key::Insert),
+    ("home", console::Key::Home),
+    ("end", console::Key::End),
+    ("page-up", console::Key::PageUp),
+    ("page-down", console::Key::PageDown),
+    ("up", console::Key::ArrowUp),
+    ("down", console::Key::ArrowDown),
+    ("left", console::Key::ArrowLeft),
+    ("right", console::Key::ArrowRight),
+];
+
+/// Why a control key can't be used, for the ones that never reach the menu as themselves.
+/// Terminals send some of them as the same character as another key, which console reads as that
+/// key, and Ctrl-C stops the program
+fn ctrl_key_problem(letter: char) -> Option<&'static str> {
+    match letter {
+        'a' => Some("it is read as `home`"),
+        'c' => Some("it interrupts the program"),
+        'e' => Some("it is read as `end`"),
+        'h' => Some("it is read as `backspace`"),
+        'i' => Some("it is read as `tab`"),
+        'j' | 'm' => Some("it is read as `enter`"),
+        _ => None,
+    }
+}
+
+/// A [`console::Key`] written as text, the way keys are given in config files and on the
+/// command line. Keys are written as the character they type (`x`), by name (`space`, `tab`,
+/// `shift-tab`, `enter`, `esc`, `backspace`, `delete`, `insert`, `home`, `end`, `page-up`,
+/// `page-down`, `up`, `down`, `left`, `right`), or as a control key (`ctrl-r`). Names aren't
+/// case sensitive, characters are.
+///
+/// `ctrl-a`, `ctrl-e`, `ctrl-h`, `ctrl-i`, `ctrl-j` and `ctrl-m` are rejected, because the menu
+/// can't tell them apart from `home`, `end`, `backspace`, `tab` and `enter`, and so is `ctrl-c`,
+/// which interrupts the program.
+///
+/// ## Example
+/// ```
+/// use cartographer_rs::KeyName;
+///
+/// let key: KeyName = "ctrl-r".parse().unwrap();
+/// assert_eq!(key.0, console::Key::Char('\x12'));
+/// assert_eq!(key.to_string(), "ctrl-r");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyName(pub console::Key);
+
+impl KeyName {
+    /// The text for the key, or `None` for keys that can't be written down, like
+    /// [`console::Key::Unknown`]
+    fn name(&self) -> Option<String> {
+        if let Some((name, _)) = KEY_NAMES.iter().find(|(_, key)| *key == self.0) {
+            return Some(name.to_string());
+        }
+        match self.0 {
+            console::Key::Char(c @ '\x01'..='\x1a') => {
+                let letter = (c as u8 + b'a' - 1) as char;
+                ctrl_key_problem(letter)
+                    .is_none()
+                    .then(|| format!("ctrl-{}", letter))
+            }
+            console::Key::Char(c) if !c.is_control() => Some(c.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for KeyName {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut chars = text.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(KeyName(console::Key::Char(c)));
+        }
+
+        let lowercase = text.trim().to_lowercase();
+        if let Some((_, key)) = KEY_NAMES.iter().find(|(name, _)| *name == lowercase) {
+            return Ok(KeyName(key.clone()));
+        }
+        if let Some(letter) = lowercase.strip_prefix("ctrl-") {
+            if let [letter @ b'a'..=b'z'] = letter.as_bytes() {
+                if let Some(problem) = ctrl_key_problem(*letter as char) {
+                    return Err(Error::Parse(format!(
+                        "`{}` can't be used as a key, as {}",
+                        text, problem
+                    )));
+                }
+                return Ok(KeyName(console::Key::Char((letter - b'a' + 1) as char)));
+            }
+        }
+        Err(Error::Parse(format!("`{}` isn't the name of a key", text)))
+    }
+}
+
+impl std::fmt::Display for KeyName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(&name),
+            None => write!(f, "{:?}", self.0),
+        }
+    }
+}
+
+impl From<console::Key> for KeyName {
+    fn from(key: console::Key) -> Self {
+        KeyName(key)
+    }
+}
+
+#[cfg(feature = "serde_serialize")]
+impl serde::Serialize for KeyName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.name() {
+            Some(name) => serializer.serialize_str(&name),
+            None => Err(serde::ser::Error::custom(format!(
+                "{:?} can't be written as a key name",
+                self.0
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "serde_serialize")]
+impl<'de> serde::Deserialize<'de> for KeyName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// (De)serializes a [`console::Key`] field as a [`KeyName`]
+#[cfg(feature = "serde_serialize")]
+pub(super) mod as_name {
+    use super::KeyName;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        key: &console::Key,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        KeyName(key.clone()).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<console::Key, D::Error> {
+        Ok(KeyName::deserialize(deserializer)?.0)
+    }
+}
+
+/// (De)serializes a pair of [`console::Key`]s as a list of two [`KeyName`]s
+#[cfg(feature = "serde_serialize")]
+pub(super) mod pair_as_names {
+    use super::KeyName;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        keys: &(console::Key, console::Key),
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        [KeyName(keys.0.clone()), KeyName(keys.1.clone())].serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<(console::Key, console::Key), D::Error> {
+        let [first, second] = <[KeyName; 2]>::deserialize(deserializer)?;
+        Ok((first.0, second.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use console::Key;
+
+    #[test]
+    fn names_round_trip() {
+        let mut keys: Vec<Key> = KEY_NAMES.iter().map(|(_, key)| key.clone()).collect();
+        keys.extend("xX7~é".chars().map(Key::Char));
+        keys.extend(['\x12', '\x0b', '\x1a'].map(Key::Char));
+
+        for key in keys {
+            let name = KeyName(key.clone()).to_string();
+            assert_eq!(
+                name.parse::<KeyName>().unwrap().0,
+                key,
+                "parsing {:?}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("ctrl-r".parse::<KeyName>().unwrap().0, Key::Char('\x12'));
+        assert_eq!("Ctrl-R".parse::<KeyName>().unwrap().0, Key::Char('\x12'));
+        assert_eq!(" Page-Up ".parse::<KeyName>().unwrap().0, Key::PageUp);
+        // Single characters are taken as they are, names are not case sensitive
+        assert_eq!("R".parse::<KeyName>().unwrap().0, Key::Char('R'));
+        assert_eq!("SPACE".parse::<KeyName>().unwrap().0, Key::Char(' '));
+
+        for name in ["", "ctrl-", "ctrl-1", "ctrl-rr", "pageup", "f1"] {
+            assert!(
+                name.parse::<KeyName>().is_err(),
+                "{:?} should not parse",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn ctrl_keys_read_as_other_keys_are_rejected() {
+        for name in [
+            "ctrl-a", "ctrl-c", "ctrl-e", "ctrl-h", "ctrl-i", "ctrl-j", "ctrl-m", "CTRL-M",
+        ] {
+            assert!(
+                name.parse::<KeyName>().is_err(),
+                "{:?} should not parse",
+                name
+            );
+        }
+
+        // Keys that can't be parsed can't be written either
+        assert_eq!(KeyName(Key::Char('\x0d')).name(), None);
+        assert_eq!(KeyName(Key::Unknown).name(), None);
+    }
+}
@@ -0,0 +1,879 @@
+use crate::CaseSensitivity;
+use crate::Column;
+use crate::MenuItem;
+use crate::MenuOptions;
+use crate::ScoreAggregation;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// The user's search input, split into the text that is fuzzy matched and scored like normal,
+/// and the terms that filter which items can be shown at all. See the
+/// [crate level docs](crate#searching) for the syntax.
+///
+/// Plain terms that aren't scoped or part of a `|` group each have to match on their own, like any
+/// other term. They are also joined back together and used to score the items, so the ranking of
+/// a search without any operators works just like it always has.
+///
+/// All of the query's text has already gone through [`Query::fold`], and it is only compared to
+/// items' [`SearchKeys`], which are folded the same way.
+pub(crate) struct Query {
+    /// The plain terms, joined back together
+    pub(crate) fuzzy: String,
+
+    /// Whether this query is case sensitive, after smart case has been taken into account
+    case_sensitive: bool,
+
+    /// Whether diacritics and compatibility characters are normalized away
+    normalize_unicode: bool,
+
+    /// The trigrams of `fuzzy`, see [`Trigrams`]
+    fuzzy_trigrams: Trigrams,
+
+    /// Groups of terms, including a group for each plain term. Every group needs at least one
+    /// term that matches
+    groups: Vec<Vec<Term>>,
+
+    /// The indexes of the columns that unscoped terms are matched against
+    searchable_columns: Vec<usize>,
+}
+
+/// A single search term that uses an operator or is scoped to a field
+struct Term {
+    /// The field the term is matched against. `None` means every searchable field
+    field: Option<Field>,
+    kind: MatchKind,
+    text: String,
+    /// The trigrams of `text`, only used by fuzzy terms
+    trigrams: Trigrams,
+    /// Set if the term should only match items that the text doesn't
+    negated: bool,
+}
+
+/// How a [`Term`]'s text is compared to a field
+#[derive(Clone, Copy, PartialEq)]
+enum MatchKind {
+    Fuzzy,
+    Contains,
+    Prefix,
+    Suffix,
+    Equals,
+}
+
+/// The part of a [`MenuItem`] that a [`Term`] looks at
+#[derive(PartialEq)]
+enum Field {
+    /// The cell of the column at this index
+    Column(usize),
+    /// The item's alternative matches
+    Alias,
+}
+
+impl Query {
+    /// Parse the user's input. `columns` are the columns of the menu being searched, so that
+    /// their headers can be used as field names
+    pub(crate) fn parse(input: &str, columns: &[Column], opts: &MenuOptions) -> Query {
+        // First, split the input into groups of terms that were joined by `|`
+        let mut raw_groups: Vec<Vec<&str>> = Vec::new();
+        let mut joining = false;
+        for word in input.split_whitespace() {
+            if word == "|" {
+                joining = !raw_groups.is_empty();
+            } else if joining {
+                raw_groups.last_mut().unwrap().push(word);
+                joining = false;
+            } else {
+                raw_groups.push(vec![word]);
+            }
+        }
+
+        let mut plain_terms = Vec::new();
+        let mut groups: Vec<Vec<Term>> = Vec::new();
+        for raw_group in raw_groups {
+            let group: Vec<Term> = raw_group
+                .iter()
+                .filter_map(|word| Term::parse(word, columns))
+                .collect();
+
+            // Lone plain terms are scored together, as well as filtering like the other terms
+            if let ([term], [word]) = (group.as_slice(), raw_group.as_slice()) {
+                if term.field.is_none() && term.kind == MatchKind::Fuzzy && !term.negated {
+                    plain_terms.push(*word);
+                }
+            }
+            // A group where every term was just an operator doesn't filter anything yet
+            if !group.is_empty() {
+                groups.push(group);
+            }
+        }
+
+        let fuzzy = plain_terms.join(" ");
+        let case_sensitive = match opts.case_sensitivity {
+            CaseSensitivity::Insensitive => false,
+            CaseSensitivity::Sensitive => true,
+            // Only the text being searched for counts, not operators or field names
+            CaseSensitivity::Smart => groups
+                .iter()
+                .flatten()
+                .any(|term| term.text.chars().any(char::is_uppercase)),
+        };
+
+        let mut query = Query {
+            fuzzy,
+            fuzzy_trigrams: Trigrams::default(),
+            case_sensitive,
+            normalize_unicode: opts.normalize_unicode,
+            groups,
+            searchable_columns: columns
+                .iter()
+                .enumerate()
+                .filter(|(_, column)| column.searchable)
+                .map(|(i, _)| i)
+                .collect(),
+        };
+
+        // Now that the settings are known, fold all of the text the user typed
+        query.fuzzy = query.fold(&query.fuzzy);
+        query.fuzzy_trigrams = Trigrams::new(&query.fuzzy);
+        for term in query.groups.iter_mut().flatten() {
+            term.text = FoldedText::new(&term.text, opts.normalize_unicode)
+                .get(case_sensitive)
+                .to_string();
+            if term.kind == MatchKind::Fuzzy {
+                term.trigrams = Trigrams::new(&term.text);
+            }
+        }
+        query
+    }
+
+    /// Prepare text to be compared with the query, according to its case and normalization
+    /// settings
+    fn fold(&self, text: &str) -> String {
+        FoldedText::new(text, self.normalize_unicode)
+            .get(self.case_sensitive)
+            .to_string()
+    }
+
+    /// Check if the user hasn't typed anything that could match or filter items yet
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fuzzy.is_empty() && self.groups.is_empty()
+    }
+
+    /// Score how well the item matches the plain terms of the query. The name and the
+    /// alternative matches (including searchable cells) are scored separately, then combined
+    /// according to `aggregation`. If there are no plain terms, every item scores 1.0, and only
+    /// the filtering terms decide what is shown
+    pub(crate) fn score(&self, keys: &SearchKeys, aggregation: ScoreAggregation) -> f32 {
+        if self.fuzzy.is_empty() {
+            return 1.0;
+        }
+
+        let label = self
+            .fuzzy_trigrams
+            .score(keys.label.get(self.case_sensitive));
+        let alternatives = keys
+            .aliases
+            .iter()
+            .chain(
+                self.searchable_columns
+                    .iter()
+                    .filter_map(|i| keys.cells.get(*i)),
+            )
+            .map(|text| self.fuzzy_trigrams.score(text.get(self.case_sensitive)))
+            .fold(0.0, f32::max);
+
+        match aggregation {
+            ScoreAggregation::Max => label.max(alternatives),
+            ScoreAggregation::Weighted {
+                label: label_weight,
+                alternatives: alternatives_weight,
+            } => (label * label_weight).max(alternatives * alternatives_weight),
+        }
+    }
+
+    /// Check if the item matches one term, taking negation into account
+    fn term_matches(&self, term: &Term, keys: &SearchKeys, threshold: f32) -> bool {
+        let found = match term.field {
+            Some(Field::Column(i)) => keys
+                .cells
+                .get(i)
+                .is_some_and(|cell| self.text_matches(term, cell, threshold)),
+            Some(Field::Alias) => keys
+                .aliases
+                .iter()
+                .any(|alias| self.text_matches(term, alias, threshold)),
+            None => std::iter::once(&keys.label)
+                .chain(&keys.aliases)
+                .chain(
+                    self.searchable_columns
+                        .iter()
+                        .filter_map(|i| keys.cells.get(*i)),
+                )
+                .any(|text| self.text_matches(term, text, threshold)),
+        };
+
+        found != term.negated
+    }
+
+    /// Compare a term's text to one field
+    fn text_matches(&self, term: &Term, field: &FoldedText, threshold: f32) -> bool {
+        let field = field.get(self.case_sensitive);
+        match term.kind {
+            MatchKind::Fuzzy => term.trigrams.score(field) > threshold,
+            MatchKind::Contains => field.contains(&term.text),
+            MatchKind::Prefix => field.starts_with(&term.text),
+            MatchKind::Suffix => field.ends_with(&term.text),
+            MatchKind::Equals => field == term.text,
+        }
+    }
+
+    /// Check if the item passes every term of the query. `threshold` is the minimum score for
+    /// fuzzy terms, which each have to reach it on their own
+    pub(crate) fn matches(&self, keys: &SearchKeys, threshold: f32) -> bool {
+        self.groups.iter().all(|group| {
+            group
+                .iter()
+                .any(|term| self.term_matches(term, keys, threshold))
+        })
+    }
+
+    /// Check if every item that passes this query's filtering terms also passed the `previous`
+    /// query's, like when the user has added a term or typed more of one. If so, only the items
+    /// that passed the previous query need to be checked against this one. A plain term only
+    /// implies the same term, as typing more of it can raise an item's score as well as lower
+    /// it, so typing another word narrows the search, but typing more of the last one doesn't
+    pub(crate) fn narrows(&self, previous: &Query) -> bool {
+        self.case_sensitive == previous.case_sensitive
+            && previous.groups.iter().all(|previous_group| {
+                self.groups.iter().any(|group| {
+                    group
+                        .iter()
+                        .all(|term| previous_group.iter().any(|other| term.implies(other)))
+                })
+            })
+    }
+}
+
+impl Term {
+    /// Check if any field that this term matches is also matched by `other`
+    fn implies(&self, other: &Term) -> bool {
+        if self.field != other.field || self.negated != other.negated {
+            return false;
+        }
+        if self.negated {
+            // Not containing "ab" means not containing "abc" either, so this goes the other way
+            other.implies_text(self)
+        } else {
+            self.implies_text(other)
+        }
+    }
+
+    /// Check if any text that this term's text and operator match is also matched by `other`'s,
+    /// ignoring negation
+    fn implies_text(&self, other: &Term) -> bool {
+        use MatchKind::*;
+        match (self.kind, other.kind) {
+            // Fuzzy terms don't imply anything, other than themselves
+            (Fuzzy, Fuzzy) => self.text == other.text,
+            (Fuzzy, _) | (_, Fuzzy) => false,
+            (_, Contains) => self.text.contains(&other.text),
+            (Prefix | Equals, Prefix) => self.text.starts_with(&other.text),
+            (Suffix | Equals, Suffix) => self.text.ends_with(&other.text),
+            (Equals, Equals) => self.text == other.text,
+            _ => false,
+        }
+    }
+}
+
+impl Term {
+    /// Parse a single word of the user's input. Returns `None` if there is no text left after
+    /// removing the operators, like when the user has only typed a `!` so far
+    fn parse(word: &str, columns: &[Column]) -> Option<Term> {
+        let (negated, word) = match word.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, word),
+        };
+
+        let (field, word) = match word.split_once(':') {
+            Some((name, rest)) => match Field::from_name(name, columns) {
+                Some(field) => (Some(field), rest),
+                None => (None, word),
+            },
+            None => (None, word),
+        };
+
+        let (kind, text) = if let Some(rest) = word.strip_prefix('\'') {
+            (MatchKind::Contains, rest)
+        } else if let Some(rest) = word.strip_prefix('^') {
+            match rest.strip_suffix('$') {
+                Some(rest) => (MatchKind::Equals, rest),
+                None => (MatchKind::Prefix, rest),
+            }
+        } else if let Some(rest) = word.strip_suffix('$') {
+            (MatchKind::Suffix, rest)
+        } else if negated || field.is_some() {
+            // Negated and scoped terms are precise, so they don't fuzzy match
+            (MatchKind::Contains, word)
+        } else {
+            (MatchKind::Fuzzy, word)
+        };
+
+        if text.is_empty() {
+            return None;
+        }
+
+        Some(Term {
+            field,
+            kind,
+            text: text.to_string(),
+            trigrams: Trigrams::default(),
+            negated,
+        })
+    }
+}
+
+impl Field {
+    /// Find the field the user meant with `name`, if there is one
+    fn from_name(name: &str, columns: &[Column]) -> Option<Field> {
+        let name = name.to_lowercase();
+        if let Some(i) = columns
+            .iter()
+            .position(|column| column.searchable && column.field_name() == name)
+        {
+            Some(Field::Column(i))
+        } else if name == "alias" {
+            Some(Field::Alias)
+        } else {
+            None
+        }
+    }
+}
+
+/// The most searches that [`PastSearches`] keeps
+const PAST_SEARCHES: usize = 8;
+
+/// Searches that have finished, along with the rows that passed each of them, most recent last.
+///
+/// Only the searches that the most recent one builds on are kept, like the search for "apple"
+/// while typing "apple pie", as those are the ones that the next search is likely to build on
+/// too. A search that builds on one of them only has to check the rows that passed it
+#[derive(Default)]
+pub(crate) struct PastSearches(Vec<(Query, Vec<usize>)>);
+
+impl PastSearches {
+    /// Remember a search that has finished, and the rows that passed it
+    pub(crate) fn push(&mut self, query: Query, passed: Vec<usize>) {
+        self.0.retain(|(past_query, _)| query.narrows(past_query));
+        if self.0.len() == PAST_SEARCHES {
+            self.0.remove(0);
+        }
+        self.0.push((query, passed));
+    }
+
+    /// Get the fewest rows that could pass `query`, if it builds on one of the past searches
+    pub(crate) fn candidates(&self, query: &Query) -> Option<&[usize]> {
+        self.0
+            .iter()
+            .filter(|(past_query, _)| query.narrows(past_query))
+            .map(|(_, passed)| passed.as_slice())
+            .min_by_key(|passed| passed.len())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Forget every past search, returning the most recent one
+    pub(crate) fn take_last(&mut self) -> Option<(Query, Vec<usize>)> {
+        let last = self.0.pop();
+        self.0.clear();
+        last
+    }
+}
+
+/// An item's searchable text, folded ahead of time so that searching doesn't have to fold every
+/// item again on every key press
+pub(crate) struct SearchKeys {
+    label: FoldedText,
+    aliases: Vec<FoldedText>,
+    /// Every cell of the item, by column
+    cells: Vec<FoldedText>,
+}
+
+impl SearchKeys {
+    pub(crate) fn new(item: &MenuItem, normalize_unicode: bool) -> SearchKeys {
+        let fold = |text: &String| FoldedText::new(text, normalize_unicode);
+        SearchKeys {
+            label: fold(&item.visible_name),
+            aliases: item
+                .alternative_matches
+                .iter()
+                .flatten()
+                .map(fold)
+                .collect(),
+            cells: item.get_cells().iter().map(fold).collect(),
+        }
+    }
+}
+
+/// Text that has been folded for both case sensitive and case insensitive searches
+struct FoldedText {
+    text: String,
+    /// The lowercase version of `text`, if it is any different
+    lowercase: Option<String>,
+}
+
+impl FoldedText {
+    fn new(text: &str, normalize_unicode: bool) -> FoldedText {
+        let text: String = if normalize_unicode {
+            // Compatibility decomposition turns full-width and other look-alike characters into
+            // their plain versions, and splits accented letters from their accents, which can
+            // then be dropped
+            text.nfkd().filter(|c| !is_combining_mark(*c)).collect()
+        } else {
+            text.to_string()
+        };
+
+        let lowercase = text.to_lowercase();
+        FoldedText {
+            lowercase: (lowercase != text).then_some(lowercase),
+            text,
+        }
+    }
+
+    fn get(&self, case_sensitive: bool) -> &str {
+        match (&self.lowercase, case_sensitive) {
+            (Some(lowercase), false) => lowercase,
+            _ => &self.text,
+        }
+    }
+}
+
+/// The trigrams of a query's text, sorted so they can be looked up quickly.
+///
+/// An item's score is the share of its text's trigrams (every group of three characters in a row)
+/// that are also in the query. This is the same score that `fuzzy_compare(text, query)` from the
+/// rust-fuzzy-search crate gives, without building the trigrams of both strings for every item
+#[derive(Default)]
+struct Trigrams(Vec<u64>);
+
+impl Trigrams {
+    fn new(query: &str) -> Trigrams {
+        let mut trigrams = Vec::new();
+        for_each_trigram(query, |trigram| trigrams.push(trigram));
+        trigrams.sort_unstable();
+        trigrams.dedup();
+        Trigrams(trigrams)
+    }
+
+    /// The share of `text`'s trigrams that are also in the query, from 0 to 1
+    fn score(&self, text: &str) -> f32 {
+        let mut matches = 0;
+        let mut total = 0;
+        for_each_trigram(text, |trigram| {
+            total += 1;
+            if self.0.binary_search(&trigram).is_ok() {
+                matches += 1;
+            }
+        });
+        matches as f32 / total as f32
+    }
+}
+
+/// Call `f` with every trigram of `text`, padded with two spaces at the start and one at the
+/// end. Each trigram's three characters are packed into one number, as a char never needs more
+/// than 21 bits
+fn for_each_trigram(text: &str, mut f: impl FnMut(u64)) {
+    let (mut first, mut second) = (' ', ' ');
+    for third in text.chars().chain(std::iter::once(' ')) {
+        f((first as u64) << 42 | (second as u64) << 21 | third as u64);
+        first = second;
+        second = third;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    const THRESHOLD: f32 = 0.005;
+
+    fn fruit() -> Vec<MenuItem> {
+        vec![
+            MenuItem::new("apple pie".to_string()),
+            MenuItem::new("banana split".to_string()),
+            MenuItem::new("apple banana bread".to_string()),
+            MenuItem::new("cherry".to_string())
+                .add_alternative_match(vec!["stone fruit".to_string()]),
+        ]
+    }
+
+    /// The names of the items that the input shows, in the order they were given
+    fn search(input: &str, items: &[MenuItem], columns: &[Column]) -> Vec<String> {
+        search_with(input, items, columns, &MenuOptions::new())
+    }
+
+    fn search_with(
+        input: &str,
+        items: &[MenuItem],
+        columns: &[Column],
+        opts: &MenuOptions,
+    ) -> Vec<String> {
+        let query = Query::parse(input, columns, opts);
+        items
+            .iter()
+            .filter(|item| {
+                let keys = SearchKeys::new(item, opts.normalize_unicode);
+                query.matches(&keys, THRESHOLD)
+                    && query.score(&keys, opts.score_aggregation) > THRESHOLD
+            })
+            .map(|item| item.visible_name.clone())
+            .collect()
+    }
+
+    /// Items made of a few words each, so that searches pass some of them and not others
+    fn many_items(count: usize) -> Vec<MenuItem> {
+        const WORDS: [&str; 8] = [
+            "apple", "banana", "cherry", "date", "elder", "fig", "grape", "honeydew",
+        ];
+        (0..count)
+            .map(|i| {
+                MenuItem::new(format!("{} {} {}", WORDS[i % 8], WORDS[i / 8 % 8], i))
+                    .add_alternative_match(vec![WORDS[i / 64 % 8].to_string()])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plain_terms_are_scored_together() {
+        let query = Query::parse("apple 'x banana ^y", &[], &MenuOptions::new());
+        assert_eq!(query.fuzzy, "apple banana");
+        assert_eq!(query.groups.len(), 4);
+    }
+
+    #[test]
+    fn plain_terms_all_have_to_match() {
+        assert_eq!(
+            search("apple banana", &fruit(), &[]),
+            ["apple banana bread"]
+        );
+        assert_eq!(
+            search("apple", &fruit(), &[]),
+            ["apple pie", "apple banana bread"]
+        );
+    }
+
+    #[test]
+    fn bar_joins_terms() {
+        let query = Query::parse("^apple | ^banana split", &[], &MenuOptions::new());
+        assert_eq!(query.groups.len(), 2);
+        assert_eq!(query.groups[0].len(), 2);
+        assert_eq!(
+            search("^apple | ^banana", &fruit(), &[]),
+            ["apple pie", "banana split", "apple banana bread"]
+        );
+        assert_eq!(
+            search("'pie | split$", &fruit(), &[]),
+            ["apple pie", "banana split"]
+        );
+        // A `|` with nothing after it yet doesn't change anything
+        assert_eq!(search("^cherry |", &fruit(), &[]), ["cherry"]);
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(
+            search("!pie", &fruit(), &[]),
+            ["banana split", "apple banana bread", "cherry"]
+        );
+        assert_eq!(
+            search("'nana", &fruit(), &[]),
+            ["banana split", "apple banana bread"]
+        );
+        assert_eq!(
+            search("^apple", &fruit(), &[]),
+            ["apple pie", "apple banana bread"]
+        );
+        assert_eq!(search("split$", &fruit(), &[]), ["banana split"]);
+        assert_eq!(search("^cherry$", &fruit(), &[]), ["cherry"]);
+        assert_eq!(search("^apple$", &fruit(), &[]), Vec::<String>::new());
+        assert_eq!(search("!^apple !pie$ !split", &fruit(), &[]), ["cherry"]);
+        // Aliases are searched too
+        assert_eq!(search("'stone", &fruit(), &[]), ["cherry"]);
+    }
+
+    #[test]
+    fn operators_without_text_are_ignored() {
+        for input in ["!", "'", "^", "$", "^$"] {
+            let query = Query::parse(input, &[], &MenuOptions::new());
+            assert!(query.is_empty(), "{:?} should be empty", input);
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let columns = [
+            Column::new("Region"),
+            Column::new("Owner").searchable(false),
+        ];
+        let items = [
+            MenuItem::new("web".to_string())
+                .cells(vec!["eu-west".to_string(), "alice".to_string()])
+                .add_alternative_match(vec!["frontend".to_string()]),
+            MenuItem::new("db".to_string()).cells(vec!["us-east".to_string(), "bob".to_string()]),
+        ];
+
+        assert_eq!(search("region:eu", &items, &columns), ["web"]);
+        assert_eq!(search("REGION:^us", &items, &columns), ["db"]);
+        assert_eq!(search("!region:eu", &items, &columns), ["db"]);
+        assert_eq!(search("alias:front", &items, &columns), ["web"]);
+        // Unsearchable columns aren't fields, so this is a plain term that matches nothing
+        assert_eq!(search("'owner:bob", &items, &columns), Vec::<String>::new());
+        // Unscoped terms look at the searchable cells as well
+        assert_eq!(search("'east", &items, &columns), ["db"]);
+        assert_eq!(search("'bob", &items, &columns), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unicode_normalization() {
+        let items = [
+            MenuItem::new("Café".to_string()),
+            MenuItem::new("ＡＢＣ Corp".to_string()),
+        ];
+        let normalized = MenuOptions::new().normalize_unicode(true);
+        assert_eq!(search_with("'cafe", &items, &[], &normalized), ["Café"]);
+        assert_eq!(search_with("'café", &items, &[], &normalized), ["Café"]);
+        assert_eq!(search_with("'ｃａｆｅ", &items, &[], &normalized), ["Café"]);
+        assert_eq!(
+            search_with("'abc", &items, &[], &normalized),
+            ["ＡＢＣ Corp"]
+        );
+        assert_eq!(search_with("cafe", &items, &[], &normalized), ["Café"]);
+
+        // Without normalizing, accents and full-width letters are different characters
+        let plain = MenuOptions::new().normalize_unicode(false);
+        assert_eq!(
+            search_with("'cafe", &items, &[], &plain),
+            Vec::<String>::new()
+        );
+        assert_eq!(search_with("'café", &items, &[], &plain), ["Café"]);
+        assert_eq!(
+            search_with("'abc", &items, &[], &plain),
+            Vec::<String>::new()
+        );
+        assert_eq!(search_with("'ＡＢＣ", &items, &[], &plain), ["ＡＢＣ Corp"]);
+    }
+
+    fn score(input: &str, item: &MenuItem, aggregation: ScoreAggregation) -> f32 {
+        let opts = MenuOptions::new();
+        let keys = SearchKeys::new(item, opts.normalize_unicode);
+        Query::parse(input, &[], &opts).score(&keys, aggregation)
+    }
+
+    #[test]
+    fn score_aggregation() {
+        let aliased = MenuItem::new("xyz".to_string())
+            .add_alternative_match(vec!["other".to_string(), "prod".to_string()]);
+        let named = MenuItem::new("prod".to_string());
+        let weak = MenuItem::new("predictor".to_string());
+
+        // An item scores as well as its best alias
+        assert_eq!(score("prod", &aliased, ScoreAggregation::Max), 1.0);
+        assert_eq!(score("prod", &named, ScoreAggregation::Max), 1.0);
+        // So an exact alias beats a name that only looks a bit like the search
+        let weak_score = score("prod", &weak, ScoreAggregation::Max);
+        assert!(weak_score > THRESHOLD && weak_score < 0.5);
+
+        let weighted = ScoreAggregation::Weighted {
+            label: 2.0,
+            alternatives: 0.5,
+        };
+        assert_eq!(score("prod", &aliased, weighted), 0.5);
+        assert_eq!(score("prod", &named, weighted), 2.0);
+        assert_eq!(score("prod", &weak, weighted), weak_score * 2.0);
+    }
+
+    #[test]
+    fn smart_case() {
+        let items = [
+            MenuItem::new("Readme".to_string()),
+            MenuItem::new("readme".to_string()),
+        ];
+        let opts = MenuOptions::new().case_sensitivity(CaseSensitivity::Smart);
+        assert_eq!(
+            search_with("'readme", &items, &[], &opts),
+            ["Readme", "readme"]
+        );
+        assert_eq!(search_with("'Readme", &items, &[], &opts), ["Readme"]);
+    }
+
+    #[test]
+    fn narrowed_searches_match_full_searches() {
+        let opts = MenuOptions::new();
+        let keys: Vec<SearchKeys> = many_items(1000)
+            .iter()
+            .map(|item| SearchKeys::new(item, opts.normalize_unicode))
+            .collect();
+        let passing = |query: &Query, candidates: &[usize]| -> Vec<usize> {
+            candidates
+                .iter()
+                .copied()
+                .filter(|i| query.matches(&keys[*i], THRESHOLD))
+                .collect()
+        };
+        let all: Vec<usize> = (0..keys.len()).collect();
+
+        for (previous, next) in [
+            ("apple", "apple ban"),
+            ("apple ban", "apple ban !fig"),
+            ("'an", "'ana"),
+            ("^ap", "^app"),
+            ("te$", "ate$"),
+            ("!an", "!a"),
+            ("apple | fig", "apple | fig ^b"),
+            ("alias:app", "alias:apple"),
+        ] {
+            let previous = Query::parse(previous, &[], &opts);
+            let next = Query::parse(next, &[], &opts);
+            assert!(next.narrows(&previous));
+
+            let narrowed = passing(&previous, &all);
+            assert!(narrowed.len() < all.len());
+            assert_eq!(passing(&next, &narrowed), passing(&next, &all));
+        }
+
+        for (previous, next) in [
+            ("appl", "apple"),
+            ("apple ban", "apple"),
+            ("'ana", "'an"),
+            ("!a", "!an"),
+            ("^app", "^app | fig"),
+        ] {
+            let previous = Query::parse(previous, &[], &opts);
+            let next = Query::parse(next, &[], &opts);
+            assert!(!next.narrows(&previous));
+        }
+    }
+
+    #[test]
+    fn past_searches_keep_what_later_searches_build_on() {
+        let opts = MenuOptions::new();
+        let parse = |input| Query::parse(input, &[], &opts);
+        let mut past_searches = PastSearches::default();
+        past_searches.push(parse("apple"), vec![1, 2, 3]);
+        past_searches.push(parse("apple ba"), vec![2, 3]);
+
+        // Typing more of "ba" can only start from the rows that passed "apple"
+        assert_eq!(
+            past_searches.candidates(&parse("apple ban")),
+            Some(&[1, 2, 3][..])
+        );
+        assert_eq!(
+            past_searches.candidates(&parse("apple ba !x")),
+            Some(&[2, 3][..])
+        );
+        assert_eq!(past_searches.candidates(&parse("appl")), None);
+
+        // "apple ba" isn't built on by "apple ban", so it is forgotten
+        past_searches.push(parse("apple ban"), vec![3]);
+        assert_eq!(
+            past_searches.candidates(&parse("apple ba !x")),
+            Some(&[1, 2, 3][..])
+        );
+
+        assert_eq!(past_searches.take_last().unwrap().1, [3]);
+        assert!(past_searches.is_empty());
+    }
+
+    #[test]
+    fn trigram_scores_match_fuzzy_compare() {
+        let texts = [
+            "",
+            "a",
+            "ab",
+            "apple",
+            "apple pie",
+            "pineapple",
+            "aaaa",
+            "Ünïcödé",
+            " spaced  out ",
+        ];
+        for query in texts {
+            let trigrams = Trigrams::new(query);
+            for text in texts {
+                assert_eq!(
+                    trigrams.score(text),
+                    rust_fuzzy_search::fuzzy_compare(text, query),
+                    "scoring {:?} against {:?}",
+                    text,
+                    query
+                );
+            }
+        }
+    }
+
+    /// A benchmark for typing into a menu with 100,000 items, run with
+    /// `cargo test --release --lib -- --ignored --nocapture search_100k_items`.
+    ///
+    /// The menu checks for key presses every 4096 rows, so that is how long a key press can go
+    /// unnoticed. That has to stay well within a frame for typing to feel smooth
+    #[test]
+    #[ignore]
+    fn search_100k_items() {
+        const ROWS: usize = 100_000;
+        const CHUNK: usize = 4096;
+        let opts = MenuOptions::new();
+        let items = many_items(ROWS);
+
+        let start = Instant::now();
+        let keys: Vec<SearchKeys> = items
+            .iter()
+            .map(|item| SearchKeys::new(item, opts.normalize_unicode))
+            .collect();
+        println!("preparing {} rows: {:?}", ROWS, start.elapsed());
+
+        let mut past_searches = PastSearches::default();
+        for input in [
+            "a",
+            "ap",
+            "app",
+            "appl",
+            "apple",
+            "apple b",
+            "apple ba",
+            "apple ban",
+            "apple ban !fig",
+        ] {
+            let start = Instant::now();
+            let query = Query::parse(input, &[], &opts);
+            let candidates = match past_searches.candidates(&query) {
+                Some(passed) => passed.to_vec(),
+                None => (0..ROWS).collect(),
+            };
+            let mut passed = Vec::new();
+            for i in candidates.iter().copied() {
+                if query.matches(&keys[i], THRESHOLD) {
+                    std::hint::black_box(query.score(&keys[i], opts.score_aggregation));
+                    passed.push(i);
+                }
+            }
+            let elapsed = start.elapsed();
+            let per_chunk = elapsed.mul_f64(CHUNK as f64 / candidates.len() as f64);
+            println!(
+                "{:16} checked {:6} rows, {:6} passed in {:?} ({:?} per {} rows)",
+                format!("{:?}", input),
+                candidates.len(),
+                passed.len(),
+                elapsed,
+                per_chunk,
+                CHUNK
+            );
+            assert!(per_chunk < Duration::from_millis(8));
+            past_searches.push(query, passed);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+/// Remembers where a menu was left, so that the next time it is served with
+/// [`Menu::serve_with`](crate::Menu::serve_with) it picks up from there: the search that was
+/// typed, the item under the cursor, and the items that were selected.
+///
+/// A session is updated however the menu is closed, including with `Esc`. Items are remembered
+/// by their [`id`](crate::MenuItem::get_id), so the menu's items can change between serves.
+/// Menus with [`only_one_selection`](crate::MenuOptions::only_one_selection) don't keep their
+/// selection, as the next pick would be mixed up with the last one.
+///
+/// With the `serde_serialize` feature, sessions can be saved and loaded, to carry them over to
+/// the next time the program runs
+///
+/// ## Example
+/// ```no_run
+/// use cartographer_rs::{menu, menu_item, MenuSession};
+///
+/// let menu = menu!("Main menu: ", [menu_item!("Status"), menu_item!("Logs"), menu_item!("Quit")]);
+/// let mut session = MenuSession::new();
+/// while let Some(selection) = menu.serve_with(&mut session).unwrap() {
+///     if selection.iter().any(|name| name == "Quit") {
+///         break;
+///     }
+///     // The cursor is still on what was just picked when the menu comes back
+/// }
+/// ```
+#[cfg_attr(
+    feature = "serde_serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MenuSession {
+    /// What was typed into the search
+    pub(crate) query: String,
+
+    /// The id of the item under the cursor
+    pub(crate) cursor: Option<String>,
+
+    /// The ids of the selected items
+    pub(crate) selected: Vec<String>,
+}
+
+impl MenuSession {
+    /// Create a session for a menu that hasn't been served yet
+    pub fn new() -> Self {
+        MenuSession::default()
+    }
+
+    /// Get the search that was typed when the menu was closed
+    pub fn get_query(&self) -> &str {
+        &self.query
+    }
+
+    /// Get the id of the item that was under the cursor when the menu was closed
+    pub fn get_cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    /// Get the ids of the items that were selected when the menu was closed
+    pub fn get_selected(&self) -> &[String] {
+        &self.selected
+    }
+
+    /// Forget the selected items, so the menu opens with nothing selected
+    pub fn clear_selection(&mut self) {
+        self.selected.clear();
+    }
+}
@@ -1,54 +1,151 @@
 /// Creates a [`MenuItem`](crate::MenuItem), filling in the defaults if values are not provided
 ///
+/// After the name, the item can be set up either with positional arguments,
+/// `(name, visible_at_rest, position, [aliases])`, or with any of these options, in any order:
+/// - `hidden` only shows the item when it matches the search. `hidden = ...` decides at run time
+/// - `pinned` keeps the item above unpinned items. `pinned = ...` decides at run time
+/// - `position = N` sets the item's [`at_rest_position`](crate::MenuItem::at_rest_position)
+/// - `aliases = ["...", ...]` adds [alternative matches](crate::MenuItem::add_alternative_match)
+/// - `id = "..."` sets the item's [`id`](crate::MenuItem::id)
+/// - `description = "..."` sets the item's [`description`](crate::MenuItem::description)
+/// - `section = "..."` sets the [`section`](crate::MenuItem::section) it is listed under
+/// - `cells = ["...", ...]` sets the item's [table cells](crate::MenuItem::cells)
+/// - `weight = N` sets the item's [`search_weight`](crate::MenuItem::search_weight)
+/// - `value = ...` gives the item a [`value`](crate::MenuItem::value) of any type
+///
 /// ## Example
 /// ```
-/// menu_item!("A Menu Item", true, 2, ["alt search"])
+/// use cartographer_rs::{menu_item, MenuItem};
+///
+/// let item = menu_item!("A Menu Item", true, 2, ["alt search"]);
 ///
 /// // Is equal to
 ///
-/// MenuItem {
-///     visible_name: "A Menu Item".to_string(),
-///     visible_at_rest: true,
-///     at_rest_position: Some(2),
-///     alternative_matches: Some(vec!["alt search"]),
-///     }
+/// assert_eq!(
+///     item,
+///     MenuItem::new("A Menu Item".to_string())
+///         .visible_at_rest(true)
+///         .at_rest_position(2)
+///         .add_alternative_match(vec!["alt search".to_string()])
+/// );
+///
+/// // Options can be given by name instead, leaving out the ones that aren't needed
+/// let item = menu_item!("A Menu Item", hidden, aliases = ["alt search"], description = "Does a thing");
+///
+/// assert_eq!(
+///     item,
+///     MenuItem::new("A Menu Item".to_string())
+///         .visible_at_rest(false)
+///         .add_alternative_match(vec!["alt search".to_string()])
+///         .description("Does a thing".to_string())
+/// );
 /// ```
+///
+/// A bare `hidden` or `pinned` after the name is always the flag, even if there is a variable of
+/// that name. Such a variable is passed with `hidden = hidden` instead
 #[macro_export]
 macro_rules! menu_item {
     ($name:expr) => {
-        {
-            use cartographer_rs::MenuItem;
-            MenuItem::new($name.to_string())
-        }
+        $crate::MenuItem::new($name.to_string())
+    };
+    ($name:expr, hidden $(, $($options:tt)*)?) => {
+        $crate::__menu_item_options!($crate::MenuItem::new($name.to_string()), hidden $(, $($options)*)?)
+    };
+    ($name:expr, pinned $(, $($options:tt)*)?) => {
+        $crate::__menu_item_options!($crate::MenuItem::new($name.to_string()), pinned $(, $($options)*)?)
+    };
+    ($name:expr, $option:ident = $($options:tt)*) => {
+        $crate::__menu_item_options!(
+            $crate::MenuItem::new($name.to_string()),
+            $option = $($options)*
+        )
     };
     ($name:expr, $visible_at_rest:expr) => {
-        {
-            use cartographer_rs::MenuItem;
-            MenuItem::new($name.to_string()).visible_at_rest($visible_at_rest)
-        }
+        $crate::MenuItem::new($name.to_string()).visible_at_rest($visible_at_rest)
     };
     ($name:expr, $visible_at_rest:expr, $default_position:expr) => {
-        {
-            use cartographer_rs::MenuItem;
-            MenuItem::new($name.to_string())
-                .visible_at_rest($visible_at_rest)
-                .at_rest_position($default_position)
-        }
+        $crate::MenuItem::new($name.to_string())
+            .visible_at_rest($visible_at_rest)
+            .at_rest_position($default_position)
     };
     ($name:expr, $visible_at_rest:expr, $default_position:expr, [$($alt_matches:expr),+]) => {
-        {
-            use cartographer_rs::MenuItem;
-            MenuItem::new($name.to_string())
-                .visible_at_rest($visible_at_rest)
-                .at_rest_position($default_position)
-                .add_alternative_match({
-                    let mut matches = Vec::<String>::new();
-                    $(
-                        matches.push($alt_matches.to_string());
-                    )+
-                    matches
-                })
-        }
+        $crate::MenuItem::new($name.to_string())
+            .visible_at_rest($visible_at_rest)
+            .at_rest_position($default_position)
+            .add_alternative_match({
+                let mut matches = ::std::vec::Vec::<::std::string::String>::new();
+                $(
+                    matches.push($alt_matches.to_string());
+                )+
+                matches
+            })
+    };
+}
+
+/// Applies the named options of [`menu_item!`](crate::menu_item!) to an item, one at a time
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __menu_item_options {
+    ($item:expr $(,)?) => {
+        $item
+    };
+    ($item:expr, hidden $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.visible_at_rest(false) $(, $($rest)*)?)
+    };
+    ($item:expr, pinned $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.pinned(true) $(, $($rest)*)?)
+    };
+    ($item:expr, hidden = $hidden:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.visible_at_rest(!$hidden) $(, $($rest)*)?)
+    };
+    ($item:expr, pinned = $pinned:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.pinned($pinned) $(, $($rest)*)?)
+    };
+    ($item:expr, position = $position:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.at_rest_position($position) $(, $($rest)*)?)
+    };
+    ($item:expr, aliases = $aliases:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!(
+            $item.add_alternative_match(
+                ::std::iter::IntoIterator::into_iter($aliases)
+                    .map(|alias| alias.to_string())
+                    .collect()
+            )
+            $(, $($rest)*)?
+        )
+    };
+    ($item:expr, id = $id:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.id($id.to_string()) $(, $($rest)*)?)
+    };
+    ($item:expr, description = $description:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.description($description.to_string()) $(, $($rest)*)?)
+    };
+    ($item:expr, section = $section:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.section($section.to_string()) $(, $($rest)*)?)
+    };
+    ($item:expr, cells = $cells:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!(
+            $item.cells(
+                ::std::iter::IntoIterator::into_iter($cells)
+                    .map(|cell| cell.to_string())
+                    .collect()
+            )
+            $(, $($rest)*)?
+        )
+    };
+    ($item:expr, weight = $weight:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.search_weight($weight) $(, $($rest)*)?)
+    };
+    ($item:expr, value = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__menu_item_options!($item.value($value) $(, $($rest)*)?)
+    };
+    ($item:expr, $option:ident $($rest:tt)*) => {
+        ::std::compile_error!(::std::concat!(
+            "`",
+            ::std::stringify!($option),
+            "` isn't an option of menu_item!, expected one of: hidden, pinned, position, aliases, \
+             id, description, section, cells, weight, value"
+        ))
     };
 }
 
@@ -56,54 +153,277 @@ macro_rules! menu_item {
 ///
 /// For the best experience, pair it with the [`menu_item!`](crate::menu_item!) macro for simple menu declaration.
 ///
-/// You can also configure the menu by passing a [`MenuOptions`](crate::MenuOptions). If this is not provided,
-/// the defaults are used instead
+/// You can also configure the menu by passing a [`MenuOptions`](crate::MenuOptions), or an
+/// `Option<MenuOptions>`. If this is not provided, the defaults are used instead
+///
+/// Besides items, the list can hold:
+/// - `section "Title" => [...]`, which puts the items in the brackets in a
+///   [`section`](crate::MenuItem::section) with a heading
+/// - `submenu item => menu`, which adds an item that opens another menu when it is picked, see
+///   [`Menu::submenu`](crate::Menu::submenu). The item can be a [`MenuItem`](crate::MenuItem)
+///   or just its name
 ///
 /// ## Example
-/// ```
+/// ```no_run
+/// use cartographer_rs::{menu, menu_item, MenuOptions};
+/// # fn main() -> Result<(), cartographer_rs::Error> {
 /// let configuration = MenuOptions::new();
 /// let menu = menu!(
 ///       "Pick a number: ",
 ///       configuration,
 ///       [
 ///           menu_item!("Item Number 1", true, 1),
-///           menu_item!("Item Number 2", true, 1),
+///           menu_item!("Item Number 2", true, 1)
 ///       ]
-/// )
+/// );
 ///
 /// let usr_choice = menu.serve()?;
-/// println!("{}", usr_choice);
+/// println!("{:?}", usr_choice);
+///
+/// let menu = menu!(
+///     "Run: ",
+///     [
+///         section "Release" => [
+///             menu_item!("Build", description = "cargo build --release"),
+///             submenu "Deploy" => menu!(
+///                 "Deploy to: ",
+///                 [menu_item!("staging"), menu_item!("production", aliases = ["prod"])]
+///             ),
+///         ],
+///         menu_item!("Quit", pinned),
+///     ]
+/// );
+/// # Ok(())
+/// # }
 /// ```
 #[macro_export]
 macro_rules! menu {
-    ( $prompt:expr, [$( $menu_item:expr ),*]) => {
+    ( $prompt:expr, [$( $entries:tt )*]) => {
+        $crate::menu!(
+            $prompt,
+            ::std::option::Option::<$crate::MenuOptions>::None,
+            [$( $entries )*]
+        )
+    };
+    ( $prompt:expr, $configuration:expr, [$( $entries:tt )*]) => {
         {
-            use cartographer_rs::{MenuItem, Menu, MenuOptions};
-            let mut items = Vec::<MenuItem>::new();
-            $(
-                items.push($menu_item);
-            )*
-            Menu::new(
-                $prompt.to_string(),
+            let configuration: ::std::option::Option<$crate::MenuOptions> =
+                ::std::convert::Into::into($configuration);
+            #[allow(unused_mut)]
+            let mut items = ::std::vec::Vec::<$crate::MenuItem>::new();
+            #[allow(unused_mut)]
+            let mut submenus = ::std::vec::Vec::<(::std::string::String, $crate::Menu)>::new();
+            $crate::__menu_entries!(
                 items,
-                Some(MenuOptions::default())
-            )
+                submenus,
+                ::std::option::Option::<&str>::None;
+                $( $entries )*
+            );
+
+            let mut menu = $crate::Menu::new($prompt.to_string(), items, configuration);
+            for (id, submenu) in submenus {
+                menu = menu.submenu(&id, submenu);
+            }
+            menu
         }
+    };
+}
 
+/// Adds the entries of a [`menu!`](crate::menu!) to its list of items and submenus, giving the
+/// items the section they are in
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __menu_entries {
+    // Plain items are pushed all at once rather than one call at a time, so that long lists
+    // don't run into the recursion limit
+    ($items:ident, $submenus:ident, $section:expr; $( $item:expr ),* $(,)?) => {
+        $( $items.push($crate::__menu_entries!(@section $item, $section)); )*
     };
-    ( $prompt:expr, $configuration:expr, [$( $menu_item:expr ),*]) => {
+    ($items:ident, $submenus:ident, $section:expr;
+        section $title:expr => [$( $inner:tt )*] $(, $( $rest:tt )*)?) => {
         {
-            use cartographer_rs::{MenuItem, Menu};
-            let mut items = Vec::<MenuItem>::new();
-            $(
-                items.push($menu_item);
-            )*
-            Menu::new(
-                $prompt.to_string(),
-                items,
-                Some($configuration)
-            )
+            let section = ::std::string::ToString::to_string(&$title);
+            $crate::__menu_entries!(
+                $items,
+                $submenus,
+                ::std::option::Option::Some(section.as_str());
+                $( $inner )*
+            );
+        }
+        $crate::__menu_entries!($items, $submenus, $section; $($( $rest )*)?);
+    };
+    ($items:ident, $submenus:ident, $section:expr;
+        submenu $item:expr => $submenu:expr $(, $( $rest:tt )*)?) => {
+        let item = $crate::__menu_entries!(@section $crate::MenuItem::from($item), $section);
+        $submenus.push((item.get_id().to_string(), $submenu));
+        $items.push(item);
+        $crate::__menu_entries!($items, $submenus, $section; $($( $rest )*)?);
+    };
+    // Plain items before a section or submenu are taken sixteen at a time, for the same reason
+    ($items:ident, $submenus:ident, $section:expr;
+        $i0:expr, $i1:expr, $i2:expr, $i3:expr, $i4:expr, $i5:expr, $i6:expr, $i7:expr,
+        $i8:expr, $i9:expr, $i10:expr, $i11:expr, $i12:expr, $i13:expr, $i14:expr, $i15:expr,
+        $( $rest:tt )*) => {
+        $crate::__menu_entries!(
+            $items,
+            $submenus,
+            $section;
+            $i0, $i1, $i2, $i3, $i4, $i5, $i6, $i7, $i8, $i9, $i10, $i11, $i12, $i13, $i14, $i15
+        );
+        $crate::__menu_entries!($items, $submenus, $section; $( $rest )*);
+    };
+    ($items:ident, $submenus:ident, $section:expr; $item:expr, $( $rest:tt )*) => {
+        $items.push($crate::__menu_entries!(@section $item, $section));
+        $crate::__menu_entries!($items, $submenus, $section; $( $rest )*);
+    };
+    (@section $item:expr, $section:expr) => {
+        match $section {
+            ::std::option::Option::Some(section) => $item.section(section.to_string()),
+            ::std::option::Option::None => $item,
         }
-
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Menu, MenuItem};
+
+    #[test]
+    fn options_by_name() {
+        let item = menu_item!(
+            "Deploy",
+            pinned = true,
+            hidden = true,
+            position = 3,
+            id = "deploy",
+            section = "Release",
+            weight = 2.0
+        );
+        assert_eq!(
+            item,
+            MenuItem::new("Deploy".to_string())
+                .pinned(true)
+                .visible_at_rest(false)
+                .at_rest_position(3)
+                .id("deploy".to_string())
+                .section("Release".to_string())
+                .search_weight(2.0)
+        );
+        assert_eq!(menu_item!("Deploy", hidden = false), menu_item!("Deploy"));
+    }
+
+    #[test]
+    fn bare_flags() {
+        assert_eq!(
+            menu_item!(
+                "Deploy",
+                hidden,
+                aliases = ["d"],
+                value = 1,
+                description = "Ship it"
+            ),
+            MenuItem::new("Deploy".to_string())
+                .visible_at_rest(false)
+                .add_alternative_match(vec!["d".to_string()])
+                .value(1)
+                .description("Ship it".to_string())
+        );
+        assert_eq!(
+            menu_item!("Deploy", pinned),
+            MenuItem::new("Deploy".to_string()).pinned(true)
+        );
+        assert_eq!(
+            menu_item!("Deploy", id = "deploy", pinned, hidden),
+            MenuItem::new("Deploy".to_string())
+                .id("deploy".to_string())
+                .pinned(true)
+                .visible_at_rest(false)
+        );
+
+        // Variables named like the flags are passed by name, or in later positions
+        let hidden = false;
+        let pinned = 4;
+        assert_eq!(menu_item!("Deploy", hidden = hidden), menu_item!("Deploy"));
+        assert_eq!(
+            menu_item!("Deploy", true, pinned),
+            MenuItem::new("Deploy".to_string()).at_rest_position(4)
+        );
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    fn long_lists() {
+        let menu = menu!("> ", [
+            menu_item!(0), menu_item!(1), menu_item!(2), menu_item!(3), menu_item!(4), menu_item!(5), menu_item!(6), menu_item!(7),
+            menu_item!(8), menu_item!(9), menu_item!(10), menu_item!(11), menu_item!(12), menu_item!(13), menu_item!(14), menu_item!(15),
+            menu_item!(16), menu_item!(17), menu_item!(18), menu_item!(19), menu_item!(20), menu_item!(21), menu_item!(22), menu_item!(23),
+            menu_item!(24), menu_item!(25), menu_item!(26), menu_item!(27), menu_item!(28), menu_item!(29), menu_item!(30), menu_item!(31),
+            menu_item!(32), menu_item!(33), menu_item!(34), menu_item!(35), menu_item!(36), menu_item!(37), menu_item!(38), menu_item!(39),
+            menu_item!(40), menu_item!(41), menu_item!(42), menu_item!(43), menu_item!(44), menu_item!(45), menu_item!(46), menu_item!(47),
+            menu_item!(48), menu_item!(49), menu_item!(50), menu_item!(51), menu_item!(52), menu_item!(53), menu_item!(54), menu_item!(55),
+            menu_item!(56), menu_item!(57), menu_item!(58), menu_item!(59), menu_item!(60), menu_item!(61), menu_item!(62), menu_item!(63),
+            menu_item!(64), menu_item!(65), menu_item!(66), menu_item!(67), menu_item!(68), menu_item!(69), menu_item!(70), menu_item!(71),
+            menu_item!(72), menu_item!(73), menu_item!(74), menu_item!(75), menu_item!(76), menu_item!(77), menu_item!(78), menu_item!(79),
+            menu_item!(80), menu_item!(81), menu_item!(82), menu_item!(83), menu_item!(84), menu_item!(85), menu_item!(86), menu_item!(87),
+            menu_item!(88), menu_item!(89), menu_item!(90), menu_item!(91), menu_item!(92), menu_item!(93), menu_item!(94), menu_item!(95),
+            menu_item!(96), menu_item!(97), menu_item!(98), menu_item!(99), menu_item!(100), menu_item!(101), menu_item!(102), menu_item!(103),
+            menu_item!(104), menu_item!(105), menu_item!(106), menu_item!(107), menu_item!(108), menu_item!(109), menu_item!(110), menu_item!(111),
+            menu_item!(112), menu_item!(113), menu_item!(114), menu_item!(115), menu_item!(116), menu_item!(117), menu_item!(118), menu_item!(119),
+            menu_item!(120), menu_item!(121), menu_item!(122), menu_item!(123), menu_item!(124), menu_item!(125), menu_item!(126), menu_item!(127),
+            menu_item!(128), menu_item!(129), menu_item!(130), menu_item!(131), menu_item!(132), menu_item!(133), menu_item!(134), menu_item!(135),
+            menu_item!(136), menu_item!(137), menu_item!(138), menu_item!(139), menu_item!(140), menu_item!(141), menu_item!(142), menu_item!(143),
+            menu_item!(144), menu_item!(145), menu_item!(146), menu_item!(147), menu_item!(148), menu_item!(149), menu_item!(150), menu_item!(151),
+            menu_item!(152), menu_item!(153), menu_item!(154), menu_item!(155), menu_item!(156), menu_item!(157), menu_item!(158), menu_item!(159),
+            menu_item!(160), menu_item!(161), menu_item!(162), menu_item!(163), menu_item!(164), menu_item!(165), menu_item!(166), menu_item!(167),
+            menu_item!(168), menu_item!(169), menu_item!(170), menu_item!(171), menu_item!(172), menu_item!(173), menu_item!(174), menu_item!(175),
+            menu_item!(176), menu_item!(177), menu_item!(178), menu_item!(179), menu_item!(180), menu_item!(181), menu_item!(182), menu_item!(183),
+            menu_item!(184), menu_item!(185), menu_item!(186), menu_item!(187), menu_item!(188), menu_item!(189), menu_item!(190), menu_item!(191),
+            menu_item!(192), menu_item!(193), menu_item!(194), menu_item!(195), menu_item!(196), menu_item!(197), menu_item!(198), menu_item!(199),
+            menu_item!(200), menu_item!(201), menu_item!(202), menu_item!(203), menu_item!(204), menu_item!(205), menu_item!(206), menu_item!(207),
+            menu_item!(208), menu_item!(209), menu_item!(210), menu_item!(211), menu_item!(212), menu_item!(213), menu_item!(214), menu_item!(215),
+            menu_item!(216), menu_item!(217), menu_item!(218), menu_item!(219), menu_item!(220), menu_item!(221), menu_item!(222), menu_item!(223),
+            menu_item!(224), menu_item!(225), menu_item!(226), menu_item!(227), menu_item!(228), menu_item!(229), menu_item!(230), menu_item!(231),
+            menu_item!(232), menu_item!(233), menu_item!(234), menu_item!(235), menu_item!(236), menu_item!(237), menu_item!(238), menu_item!(239),
+            menu_item!(240), menu_item!(241), menu_item!(242), menu_item!(243), menu_item!(244), menu_item!(245), menu_item!(246), menu_item!(247),
+            menu_item!(248), menu_item!(249),
+            section "More" => [
+                menu_item!(250), menu_item!(251), menu_item!(252), menu_item!(253), menu_item!(254), menu_item!(255), menu_item!(256), menu_item!(257),
+                menu_item!(258), menu_item!(259), menu_item!(260), menu_item!(261), menu_item!(262), menu_item!(263), menu_item!(264), menu_item!(265),
+                menu_item!(266), menu_item!(267), menu_item!(268), menu_item!(269), menu_item!(270), menu_item!(271), menu_item!(272), menu_item!(273),
+                menu_item!(274), menu_item!(275), menu_item!(276), menu_item!(277), menu_item!(278), menu_item!(279), menu_item!(280), menu_item!(281),
+                menu_item!(282), menu_item!(283), menu_item!(284), menu_item!(285), menu_item!(286), menu_item!(287), menu_item!(288), menu_item!(289),
+                menu_item!(290), menu_item!(291), menu_item!(292), menu_item!(293), menu_item!(294), menu_item!(295), menu_item!(296), menu_item!(297),
+                menu_item!(298), menu_item!(299), menu_item!(300), menu_item!(301), menu_item!(302), menu_item!(303), menu_item!(304), menu_item!(305),
+                menu_item!(306), menu_item!(307), menu_item!(308), menu_item!(309), menu_item!(310), menu_item!(311), menu_item!(312), menu_item!(313),
+                menu_item!(314), menu_item!(315), menu_item!(316), menu_item!(317), menu_item!(318), menu_item!(319), menu_item!(320), menu_item!(321),
+                menu_item!(322), menu_item!(323), menu_item!(324), menu_item!(325), menu_item!(326), menu_item!(327), menu_item!(328), menu_item!(329),
+                menu_item!(330), menu_item!(331), menu_item!(332), menu_item!(333), menu_item!(334), menu_item!(335), menu_item!(336), menu_item!(337),
+                menu_item!(338), menu_item!(339), menu_item!(340), menu_item!(341), menu_item!(342), menu_item!(343), menu_item!(344), menu_item!(345),
+                menu_item!(346), menu_item!(347), menu_item!(348), menu_item!(349), menu_item!(350), menu_item!(351), menu_item!(352), menu_item!(353),
+                menu_item!(354), menu_item!(355), menu_item!(356), menu_item!(357), menu_item!(358), menu_item!(359), menu_item!(360), menu_item!(361),
+                menu_item!(362), menu_item!(363), menu_item!(364), menu_item!(365), menu_item!(366), menu_item!(367), menu_item!(368), menu_item!(369),
+                menu_item!(370), menu_item!(371), menu_item!(372), menu_item!(373), menu_item!(374), menu_item!(375), menu_item!(376), menu_item!(377),
+                menu_item!(378), menu_item!(379), menu_item!(380), menu_item!(381), menu_item!(382), menu_item!(383), menu_item!(384), menu_item!(385),
+                menu_item!(386), menu_item!(387), menu_item!(388), menu_item!(389), menu_item!(390), menu_item!(391), menu_item!(392), menu_item!(393),
+                menu_item!(394), menu_item!(395), menu_item!(396), menu_item!(397), menu_item!(398), menu_item!(399), menu_item!(400), menu_item!(401),
+                menu_item!(402), menu_item!(403), menu_item!(404), menu_item!(405), menu_item!(406), menu_item!(407), menu_item!(408), menu_item!(409),
+                menu_item!(410), menu_item!(411), menu_item!(412), menu_item!(413), menu_item!(414), menu_item!(415), menu_item!(416), menu_item!(417),
+                menu_item!(418), menu_item!(419), menu_item!(420), menu_item!(421), menu_item!(422), menu_item!(423), menu_item!(424), menu_item!(425),
+                menu_item!(426), menu_item!(427), menu_item!(428), menu_item!(429), menu_item!(430), menu_item!(431), menu_item!(432), menu_item!(433),
+                menu_item!(434), menu_item!(435), menu_item!(436), menu_item!(437), menu_item!(438), menu_item!(439), menu_item!(440), menu_item!(441),
+                menu_item!(442), menu_item!(443), menu_item!(444), menu_item!(445), menu_item!(446), menu_item!(447), menu_item!(448), menu_item!(449),
+                menu_item!(450), menu_item!(451), menu_item!(452), menu_item!(453), menu_item!(454), menu_item!(455), menu_item!(456), menu_item!(457),
+                menu_item!(458), menu_item!(459), menu_item!(460), menu_item!(461), menu_item!(462), menu_item!(463), menu_item!(464), menu_item!(465),
+                menu_item!(466), menu_item!(467), menu_item!(468), menu_item!(469), menu_item!(470), menu_item!(471), menu_item!(472), menu_item!(473),
+                menu_item!(474), menu_item!(475), menu_item!(476), menu_item!(477), menu_item!(478), menu_item!(479), menu_item!(480), menu_item!(481),
+                menu_item!(482), menu_item!(483), menu_item!(484), menu_item!(485), menu_item!(486), menu_item!(487), menu_item!(488), menu_item!(489),
+                menu_item!(490), menu_item!(491), menu_item!(492), menu_item!(493), menu_item!(494), menu_item!(495), menu_item!(496), menu_item!(497),
+                menu_item!(498), menu_item!(499),
+            ],
+        ]);
+
+        let mut items: Vec<_> = (0..250).map(|i| MenuItem::new(i.to_string())).collect();
+        items.extend((250..500).map(|i| MenuItem::new(i.to_string()).section("More".to_string())));
+        assert_eq!(menu, Menu::new("> ".to_string(), items, None));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::MenuItem;
+
+/// An item the user selected, returned by [`Menu::serve_selection`](crate::Menu::serve_selection)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selected {
+    index: usize,
+    item: MenuItem,
+}
+
+impl Selected {
+    pub(crate) fn new(index: usize, item: MenuItem) -> Self {
+        Selected { index, item }
+    }
+
+    /// Where the item is in the menu's list of items, counting streamed items after the menu's
+    /// own, in the order they arrived. For a menu with a [`source`](crate::Menu::source), it's
+    /// where the item was in the list the source returned
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The item that was selected
+    pub fn item(&self) -> &MenuItem {
+        &self.item
+    }
+
+    /// The text the item is shown with in the menu
+    pub fn label(&self) -> &str {
+        self.item.name()
+    }
+}
+
+/// The shapes a selection can be written in, for printing it or passing it on to other programs
+///
+/// ## Example
+/// ```no_run
+/// use cartographer_rs::{menu, menu_item, OutputFormat};
+///
+/// let menu = menu!("Delete: ", [menu_item!("notes.txt"), menu_item!("old report.pdf")]);
+/// if let Some(selection) = menu.serve_selection().unwrap() {
+///     // Safe to pipe into `xargs -0 rm`
+///     print!("{}", OutputFormat::Nul.format(&selection));
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Each label followed by a newline
+    Lines,
+    /// Each label followed by a NUL byte, for `xargs -0` and other programs that read NUL
+    /// separated input. Unlike newlines, NUL can't be part of a file name or a command line
+    /// argument, so labels that are either of those come out whole. A label with a NUL in it
+    /// would be read as two
+    Nul,
+    /// The labels joined by a delimiter, like `", "`
+    Delimited(String),
+    /// A JSON array of the labels
+    JsonLabels,
+    /// A JSON array of the items' [`index`](Selected::index)es
+    JsonIndices,
+    /// A JSON array of the items' [`id`](MenuItem::id)s, which can carry a payload for each item,
+    /// like a key into a database
+    JsonIds,
+    /// The labels quoted for a POSIX shell and separated by spaces, so they can be pasted into
+    /// a command line
+    ShellQuoted,
+}
+
+impl OutputFormat {
+    /// Write a selection in this format
+    pub fn format(&self, selection: &[Selected]) -> String {
+        let labels = selection.iter().map(Selected::label);
+        match self {
+            OutputFormat::Lines => labels.map(|label| format!("{}\n", label)).collect(),
+            OutputFormat::Nul => labels.map(|label| format!("{}\0", label)).collect(),
+            OutputFormat::Delimited(delimiter) => labels.collect::<Vec<&str>>().join(delimiter),
+            OutputFormat::JsonLabels => json_array(labels.map(json_string)),
+            OutputFormat::JsonIndices => {
+                json_array(selection.iter().map(|selected| selected.index.to_string()))
+            }
+            OutputFormat::JsonIds => json_array(
+                selection
+                    .iter()
+                    .map(|selected| json_string(selected.item.get_id())),
+            ),
+            OutputFormat::ShellQuoted => labels.map(shell_quote).collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+fn json_array(values: impl Iterator<Item = String>) -> String {
+    format!("[{}]", values.collect::<Vec<String>>().join(","))
+}
+
+/// Write text as a JSON string, quoted and escaped
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Quote text for a POSIX shell, leaving it as is if it doesn't need quoting
+fn shell_quote(text: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-./,:=@%+".contains(c);
+    if !text.is_empty() && text.chars().all(plain) {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Text that has to be escaped or quoted one way or another
+    const AWKWARD: [&str; 9] = [
+        "",
+        "it's",
+        "back\\slash",
+        "say \"hi\"",
+        "two\nlines",
+        "bell\u{7}, tab\t, return\r, escape\u{1b}",
+        "naïve café 日本",
+        "$HOME `ls` *.rs",
+        "-rf",
+    ];
+
+    fn selection(labels: &[&str]) -> Vec<Selected> {
+        labels
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let item = MenuItem::new(label.to_string()).id(format!("id {}", i));
+                Selected::new(i * 2, item)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string(""), "\"\"");
+        assert_eq!(json_string("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(json_string("back\\slash"), "\"back\\\\slash\"");
+        assert_eq!(json_string("a\nb\r\t"), "\"a\\nb\\r\\t\"");
+        assert_eq!(json_string("\u{7}\u{1f}"), "\"\\u0007\\u001f\"");
+        assert_eq!(json_string("naïve 日本"), "\"naïve 日本\"");
+
+        for text in AWKWARD {
+            let parsed: String = serde_json::from_str(&json_string(text)).unwrap();
+            assert_eq!(parsed, text);
+        }
+    }
+
+    #[test]
+    fn shell_quoting() {
+        assert_eq!(shell_quote("notes.txt"), "notes.txt");
+        assert_eq!(shell_quote("a=b,c:d@e%f+g"), "a=b,c:d@e%f+g");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote("back\\slash"), "'back\\slash'");
+        assert_eq!(shell_quote("two\nlines"), "'two\nlines'");
+        assert_eq!(shell_quote("café"), "'café'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shell_quoted_text_comes_back_whole() {
+        for text in AWKWARD {
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(format!("printf '%s' {}", shell_quote(text)))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let selection = selection(&["notes.txt", "it's \"new\"", "two\nlines"]);
+        let format = |format: OutputFormat| format.format(&selection);
+
+        assert_eq!(
+            format(OutputFormat::Lines),
+            "notes.txt\nit's \"new\"\ntwo\nlines\n"
+        );
+        assert_eq!(
+            format(OutputFormat::Nul),
+            "notes.txt\0it's \"new\"\0two\nlines\0"
+        );
+        assert_eq!(
+            format(OutputFormat::Delimited(", ".to_string())),
+            "notes.txt, it's \"new\", two\nlines"
+        );
+        assert_eq!(
+            format(OutputFormat::JsonLabels),
+            r#"["notes.txt","it's \"new\"","two\nlines"]"#
+        );
+        assert_eq!(format(OutputFormat::JsonIndices), "[0,2,4]");
+        assert_eq!(format(OutputFormat::JsonIds), r#"["id 0","id 1","id 2"]"#);
+        assert_eq!(
+            format(OutputFormat::ShellQuoted),
+            "notes.txt 'it'\\''s \"new\"' 'two\nlines'"
+        );
+
+        // An empty selection is written as nothing, or an empty array
+        assert_eq!(OutputFormat::Lines.format(&[]), "");
+        assert_eq!(OutputFormat::JsonLabels.format(&[]), "[]");
+    }
+}
@@ -0,0 +1,69 @@
+use cartographer_rs::{MenuEnum, MenuItem};
+
+#[derive(Debug, PartialEq, MenuEnum)]
+enum Environment {
+    #[menu(label = "Staging", position = 1)]
+    Staging,
+    #[menu(label = "Production", aliases = ["prod", "live"])]
+    Production,
+    #[menu(hidden)]
+    Local,
+}
+
+#[test]
+fn items_follow_the_attributes() {
+    assert_eq!(
+        Environment::menu_items(),
+        vec![
+            MenuItem::new("Staging".to_string())
+                .id("Staging".to_string())
+                .at_rest_position(1),
+            MenuItem::new("Production".to_string())
+                .id("Production".to_string())
+                .add_alternative_match(vec!["prod".to_string(), "live".to_string()]),
+            MenuItem::new("Local".to_string())
+                .id("Local".to_string())
+                .visible_at_rest(false),
+        ]
+    );
+}
+
+#[test]
+fn indexes_map_to_variants() {
+    assert_eq!(Environment::from_index(0), Some(Environment::Staging));
+    assert_eq!(Environment::from_index(1), Some(Environment::Production));
+    assert_eq!(Environment::from_index(2), Some(Environment::Local));
+    assert_eq!(Environment::from_index(3), None);
+}
+
+/// Stands in for another crate's re-export of cartographer-rs. It has its own `MenuEnum`, so the
+/// test below only compiles if the derive implements the trait at the path it was given
+mod reexport {
+    pub use cartographer_rs::MenuItem;
+
+    pub trait MenuEnum: Sized {
+        fn menu_items() -> Vec<MenuItem>;
+        fn from_index(index: usize) -> Option<Self>;
+    }
+}
+
+#[derive(Debug, PartialEq, MenuEnum)]
+#[menu(crate = reexport)]
+enum Renamed {
+    First,
+    #[menu(label = "Second one")]
+    Second,
+}
+
+#[test]
+fn crate_path_can_be_changed() {
+    let labels: Vec<String> = <Renamed as reexport::MenuEnum>::menu_items()
+        .iter()
+        .map(|item| item.name().to_string())
+        .collect();
+    assert_eq!(labels, ["First", "Second one"]);
+    assert_eq!(
+        <Renamed as reexport::MenuEnum>::from_index(1),
+        Some(Renamed::Second)
+    );
+}